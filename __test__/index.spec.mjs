@@ -1,12 +1,29 @@
 import fs from 'fs/promises'
 import test from 'ava'
 
-import {decode, encode} from '../index.js'
+import {BarcodeFormat, VideoScanner, classifyInput, decode, decodeText, decodeWithBinarizer, encode, encodePdfBatch, encodePreviewSheet, encodeWithMetadata, encodeWithTransform, estimateEncodedSize, verifySheet} from '../index.js'
 import * as url from 'url';
 import path from 'path';
 
 const __dirname = url.fileURLToPath(new URL('.', import.meta.url));
 
+// Warnings are emitted asynchronously, so wait for the expected one rather than whichever comes next, and fail
+// rather than hang the suite if it never comes
+const nextWarning = (pattern, timeout = 10000) => new Promise((resolve, reject) => {
+    const listener = (warning) => {
+        if (pattern.test(warning.message)) {
+            clearTimeout(timer)
+            process.off('warning', listener)
+            resolve(warning)
+        }
+    }
+    const timer = setTimeout(() => {
+        process.off('warning', listener)
+        reject(new Error(`no warning matching ${pattern} was emitted`))
+    }, timeout)
+    process.on('warning', listener)
+})
+
 test('encode', async (t) => {
     const result = encode('hello, world')
     await fs.writeFile(path.join(__dirname, '__qrcode.jpg'), result)
@@ -18,6 +35,266 @@ test('encode output to file', async (t) => {
     t.is(decode(path.join(__dirname, '__output.jpg')).text, 'hello, world')
 })
 
+test('encode with knockout', (t) => {
+    const knockout = [{ x: 90, y: 90, width: 25, height: 25 }]
+    const result = encode('hello, world', { errorCorrection: 'H', margin: 2, knockout, verify: true })
+    t.is(decode(result.toString('base64')).text, 'hello, world')
+})
+
+test('encode with unreadable knockout fails verification', (t) => {
+    const knockout = [{ x: 0, y: 0, width: 200, height: 100 }]
+    t.is(encode('hello, world', { knockout, verify: true }), null)
+})
+
+test('encode ean and upc without check digit with verification', (t) => {
+    const options = { width: 300, height: 100, margin: 20, verify: true }
+    t.truthy(encode('123456789012', { ...options, barcodeFormat: BarcodeFormat.Ean13 }))
+    t.truthy(encode('1234567', { ...options, barcodeFormat: BarcodeFormat.Ean8 }))
+    t.truthy(encode('12345678901', { ...options, barcodeFormat: BarcodeFormat.UpcA }))
+    t.truthy(encode('1234565', { ...options, barcodeFormat: BarcodeFormat.UpcE }))
+})
+
+// Image width, from the SOF0 header of a baseline JPEG
+const jpegWidth = (jpeg) => jpeg.readUInt16BE(jpeg.indexOf(Buffer.from([0xff, 0xc0])) + 7)
+
+test('encode without quiet zone', async (t) => {
+    const options = { barcodeFormat: BarcodeFormat.Ean13, width: 300, height: 100 }
+    const warning = nextWarning(/noQuietZone crops the code below the quiet zone/)
+    const flush = encode('123456789012', { ...options, noQuietZone: true })
+    const padded = encode('123456789012', options)
+    t.true(jpegWidth(flush) < jpegWidth(padded))
+    t.truthy(await warning)
+})
+
+test('encode with automatic margin', (t) => {
+    const options = { barcodeFormat: BarcodeFormat.Code128, width: 60, height: 60 }
+    t.is(decode(`data:image/jpeg;base64,${encode('ABC123', options).toString('base64')}`), null)
+
+    const { image, margin } = encodeWithMetadata('ABC123', { ...options, autoMargin: true })
+    // Code 128 needs 10 modules of quiet zone on each side
+    t.true(margin >= 20)
+    t.is(decode(`data:image/jpeg;base64,${image.toString('base64')}`).text, 'ABC123')
+    t.true(encodeWithMetadata('hello, world', { autoMargin: true }).margin >= 4)
+
+    const narrow = encodeWithMetadata('ABC123', { ...options, autoMargin: true, allowNarrowQuietZone: true })
+    t.true(narrow.margin > 0 && narrow.margin < 20)
+    t.is(decode(`data:image/jpeg;base64,${narrow.image.toString('base64')}`).text, 'ABC123')
+    t.is(encodeWithMetadata('ABC123', { ...options, autoMargin: true, noQuietZone: true }), null)
+
+    // The knockout covers a finder pattern until the margin is wide enough to shrink the code out of its way
+    const knockout = (size) => ({ width: 200, knockout: [{ x: 0, y: 0, width: size, height: size }], autoMargin: true })
+    t.is(decode(encode('hello, world', { ...knockout(50), autoMargin: false, margin: 4 }).toString('base64')), null)
+    const widened = encodeWithMetadata('hello, world', knockout(50))
+    t.true(widened.margin > 4 && widened.margin <= 12)
+    t.is(decode(widened.image.toString('base64')).text, 'hello, world')
+    t.is(encodeWithMetadata('hello, world', knockout(70)), null)
+})
+
+test('estimate encoded size', (t) => {
+    for (const options of [{ width: 400 }, { width: 400, outputFormat: 'png' }, { barcodeFormat: BarcodeFormat.Code128, width: 500, height: 150 }]) {
+        const ratio = estimateEncodedSize('hello, world', options) / encode('hello, world', options).length
+        t.true(ratio > 0.5 && ratio < 2)
+    }
+    t.is(estimateEncodedSize('hello, world', { outputFormat: 'gif' }), null)
+})
+
+test('encode pdf batch', (t) => {
+    const pdf = encodePdfBatch([{ data: 'VOUCHER-1', caption: 'Voucher #1' }, { data: 'VOUCHER-2' }])
+    t.is(pdf.subarray(0, 8).toString(), '%PDF-1.4')
+    t.is(pdf.toString('latin1').match(/\/Type \/Page /g).length, 2)
+})
+
+test('encode preview sheet', (t) => {
+    const { image, formats, skipped } = encodePreviewSheet('hello', { outputFormat: 'png' })
+    t.is(image.subarray(1, 4).toString(), 'PNG')
+    t.true(formats.includes(BarcodeFormat.QrCode) && formats.includes(BarcodeFormat.Code128))
+    // EAN and UPC codes only hold digits
+    t.true(skipped.includes(BarcodeFormat.Ean13) && skipped.includes(BarcodeFormat.UpcA))
+    t.is(formats.length + skipped.length, 13)
+    t.is(decode(image.toString('base64')).text, 'hello')
+    t.is(encodePreviewSheet(''), null)
+})
+
+test('encode png with payload metadata', (t) => {
+    const png = encode('hello, world', { embedPayloadMetadata: true })
+    const text = {}
+    for (let offset = 8; offset < png.length;) {
+        const length = png.readUInt32BE(offset)
+        if (png.toString('latin1', offset + 4, offset + 8) === 'tEXt') {
+            const chunk = png.subarray(offset + 8, offset + 8 + length)
+            const separator = chunk.indexOf(0)
+            text[chunk.toString('latin1', 0, separator)] = chunk.toString('latin1', separator + 1)
+        }
+        offset += 12 + length
+    }
+    t.deepEqual(text, { 'Barcode Data': 'hello, world', 'Barcode Format': 'qrcode' })
+    t.is(decode(png.toString('base64')).text, 'hello, world')
+})
+
+test('encode with row colors', (t) => {
+    const result = encode('hello, world', { rowColors: ['#c00000', '#0000aa'], margin: 2, verify: true })
+    t.is(decode(result.toString('base64')).text, 'hello, world')
+    t.is(encode('hello, world', { rowColors: ['#ffff00'] }), null)
+
+    // Each color is dark enough on its own, but the gray rows read as light next to the black ones
+    const mixed = { rowColors: ['#000000', '#949494'], width: 200, margin: 4 }
+    t.is(encode('hello, world', mixed), null)
+    t.truthy(encode('hello, world', { ...mixed, allowLowContrast: true }))
+})
+
+const bmp = (width, height, color) => {
+    const stride = Math.ceil((width * 3) / 4) * 4
+    const bytes = Buffer.alloc(54 + stride * height)
+    bytes.write('BM')
+    bytes.writeUInt32LE(bytes.length, 2)
+    bytes.writeUInt32LE(54, 10)
+    bytes.writeUInt32LE(40, 14)
+    bytes.writeInt32LE(width, 18)
+    bytes.writeInt32LE(-height, 22)
+    bytes.writeUInt16LE(1, 26)
+    bytes.writeUInt16LE(24, 28)
+    for (let y = 0; y < height; y++) {
+        for (let x = 0; x < width; x++) {
+            const [r, g, b] = color(x, y)
+            bytes.set([b, g, r], 54 + y * stride + x * 3)
+        }
+    }
+    return bytes
+}
+
+test('encode with low contrast colors', async (t) => {
+    const warning = nextWarning(/rowColors .*allowLowContrast/)
+    t.is(encode('hello, world', { rowColors: ['#ffff00'] }), null)
+    t.truthy(await warning)
+
+    const allowed = nextWarning(/rowColors .*may not scan reliably/)
+    t.truthy(encode('hello, world', { rowColors: ['#ffff00'], allowLowContrast: true }))
+    t.truthy(await allowed)
+})
+
+test('encode with background image', async (t) => {
+    const light = bmp(16, 16, (x, y) => ((x + y) % 2 ? [255, 240, 200] : [230, 220, 250]))
+    const result = encode('hello, world', { backgroundImage: light, outputFormat: 'png' })
+    t.is(decode(result.toString('base64')).text, 'hello, world')
+
+    const warning = nextWarning(/backgroundImage .*contrast/)
+    const dark = bmp(16, 16, (x, y) => ((x + y) % 2 ? [40, 40, 40] : [230, 220, 250]))
+    t.is(encode('hello, world', { backgroundImage: dark }), null)
+    t.truthy(await warning)
+})
+
+test('encode with module opacity', async (t) => {
+    const faint = encode('hello, world', { moduleOpacity: 0.6 })
+    t.is(decode(faint.toString('base64')).text, 'hello, world')
+
+    const warning = nextWarning(/moduleOpacity .*allowLowContrast/)
+    t.is(encode('hello, world', { moduleOpacity: 0.3 }), null)
+    t.truthy(await warning)
+    t.truthy(encode('hello, world', { moduleOpacity: 0.4, allowLowContrast: true }))
+    t.is(encode('hello, world', { moduleOpacity: 1.5 }), null)
+})
+
+test('encode with transform', async (t) => {
+    let modules
+    const marked = encodeWithTransform('hello, world', ({ width, height, data }) => {
+        modules = { width, height }
+        // Flip the center module, which error correction recovers from
+        data[220] = !data[220]
+        return { width, height, data }
+    }, { width: 200, height: 200, margin: 4, outputFormat: 'png' })
+    t.deepEqual(modules, { width: 21, height: 21 })
+    t.is(decode(marked.toString('base64')).text, 'hello, world')
+    t.notDeepEqual(marked, encode('hello, world', { width: 200, height: 200, margin: 4, outputFormat: 'png' }))
+
+    const resized = nextWarning(/must keep its size of 21x21/)
+    t.is(encodeWithTransform('hello, world', ({ width, height, data }) => ({ width, height: height - 1, data }), { width: 200, height: 200 }), null)
+    t.truthy(await resized)
+
+    const unreadable = nextWarning(/no longer decodes/)
+    t.is(encodeWithTransform('hello, world', ({ width, height, data }) => ({ width, height, data: data.map(() => false) })), null)
+    t.truthy(await unreadable)
+
+    t.throws(() => encodeWithTransform('hello, world', () => { throw new Error('bad matrix') }), { message: 'bad matrix' })
+})
+
+test('encode with matrix', (t) => {
+    const { image, matrix } = encodeWithMetadata('hello, world', { width: 200, height: 200, includeMatrix: true })
+    t.is(decode(image.toString('base64')).text, 'hello, world')
+    // A version 1 QR code, one cell per module
+    t.is(matrix.width, 21)
+    t.is(matrix.height, 21)
+    t.is(matrix.data.length, 21 * 21)
+
+    // Rendered again from the matrix alone, at 8 pixels per module with a 4 module quiet zone
+    const dark = (x, y) => x >= 0 && y >= 0 && x < matrix.width && y < matrix.height && matrix.data[y * matrix.width + x]
+    const rendered = bmp(232, 232, (x, y) => (dark(Math.floor(x / 8) - 4, Math.floor(y / 8) - 4) ? [0, 0, 0] : [255, 255, 255]))
+    t.is(decode(rendered.toString('base64')).text, 'hello, world')
+
+    const linear = encodeWithMetadata('ABC123', { barcodeFormat: BarcodeFormat.Code128, includeMatrix: true }).matrix
+    t.is(linear.height, 1)
+    t.is(linear.data.length, linear.width)
+    t.is(encodeWithMetadata('hello, world').matrix, undefined)
+})
+
+test('encode with finder color', (t) => {
+    const result = encode('hello, world', { finderColor: '#cc0000', rowColors: ['#333333'] })
+    t.is(decode(result.toString('base64')).text, 'hello, world')
+    t.is(encode('hello, world', { finderColor: '#ffff00' }), null)
+    t.is(encode('1234567', { finderColor: '#cc0000', barcodeFormat: BarcodeFormat.Ean8 }), null)
+})
+
+test('encode with simulated degradation', async (t) => {
+    const warnings = []
+    const listener = (warning) => {
+        if (/simulated dot gain/.test(warning.message)) {
+            warnings.push(warning)
+        }
+    }
+    process.on('warning', listener)
+
+    t.true(encodeWithMetadata('hello, world', { simulateDegradation: true }).degradationPassed)
+    // Fragile, but still returned
+    const fragile = encodeWithMetadata('hello, world', { knockout: [{ x: 80, y: 80, width: 39, height: 39 }], simulateDegradation: true })
+    t.truthy(fragile.image)
+    t.false(fragile.degradationPassed)
+    t.is(encodeWithMetadata('hello, world').degradationPassed, undefined)
+
+    await new Promise((resolve) => setTimeout(resolve, 50))
+    process.off('warning', listener)
+    t.is(warnings.length, 1)
+})
+
+test('encode gs1 data matrix with fnc1 separators', (t) => {
+    const options = { barcodeFormat: BarcodeFormat.DataMatrix, fnc1Placeholder: '|' }
+    const result = decode(encode('0109501101020917|10ABC123|2110', options).toString('base64'))
+    t.is(result.text, '0109501101020917\x1D10ABC123\x1D2110')
+    t.is(result.metadata.SYMBOLOGY_IDENTIFIER, ']d2')
+    t.is(decode(encode('|0109501101020917', options).toString('base64')).text, '0109501101020917')
+    t.is(encode('0109501101020917|10ABC', { fnc1Placeholder: '|' }), null)
+})
+
+test('encode itf with an odd digit count', async (t) => {
+    const options = { barcodeFormat: BarcodeFormat.ITF, width: 300, height: 100 }
+    const warning = nextWarning(/even number of digits/)
+    t.is(encode('12345', options), null)
+    t.truthy(await warning)
+
+    const padded = encode('12345', { ...options, itfPadding: 'leading-zero' })
+    t.is(decode(padded.toString('base64')).text, '012345')
+})
+
+test('encode itf with an even digit count', (t) => {
+    const options = { barcodeFormat: BarcodeFormat.ITF, width: 300, height: 100 }
+    t.is(decode(encode('123456', options).toString('base64')).text, '123456')
+    t.is(decode(encode('123456', { ...options, itfPadding: 'leading-zero' }).toString('base64')).text, '123456')
+})
+
+test('encode itf with an unknown padding', async (t) => {
+    const warning = nextWarning(/itfPadding must be/)
+    t.is(encode('123456', { barcodeFormat: BarcodeFormat.ITF, width: 300, height: 100, itfPadding: 'bogus' }), null)
+    t.truthy(await warning)
+})
+
 test('decode', (t) => {
     t.is(decode(path.join(__dirname, 'qrcode.jpg')).text, 'hello, world')
 })
@@ -31,3 +308,305 @@ test('decode base64 data url', async (t) => {
     const base64 = await fs.readFile(path.join(__dirname, 'qrcode.jpg'), { encoding: 'base64' });
     t.is(decode(`data:image/jpeg;base64,${base64}`).text, 'hello, world')
 })
+
+test('decode format code and name', (t) => {
+    const result = decode(path.join(__dirname, 'qrcode.jpg'))
+    t.is(result.format, BarcodeFormat.QrCode)
+    t.is(result.formatCode, 11)
+    t.is(result.formatName, 'qrcode')
+})
+
+test('decode estimate dpi', (t) => {
+    const result = decode(encode('hello, world', { width: 200, margin: 1 }).toString('base64'), { estimateDpi: { xDimensionMm: 0.5 } })
+    t.is(result.moduleSizePx, 8)
+    t.is(Math.round(result.estimatedDpi), 406)
+})
+
+test('decode in parallel stripes', (t) => {
+    // The code is centered, across the boundary between the second and third stripes
+    const tall = encode('hello, world', { width: 200, height: 1000 }).toString('base64')
+    t.is(decode(tall, { parallelStripes: 4, stripeOverlap: 0 }), null)
+    t.is(decode(tall, { parallelStripes: 4 }).text, 'hello, world')
+    // Stripes of 84 rows with the default overlap are shorter than the 200 rows code
+    t.is(decode(tall, { parallelStripes: 12 }), null)
+    t.is(decode(tall, { parallelStripes: 12, stripeOverlap: 200 }).text, 'hello, world')
+    t.is(decode(path.join(__dirname, 'qrcode.jpg'), { parallelStripes: 100, stripeOverlap: 200 }).text, 'hello, world')
+})
+
+test('classify input', async (t) => {
+    const base64 = await fs.readFile(path.join(__dirname, 'qrcode.jpg'), { encoding: 'base64' });
+    t.is(classifyInput(path.join(__dirname, 'qrcode.jpg')), 'file')
+    t.is(classifyInput(base64), 'base64')
+    t.is(classifyInput(`data:image/jpeg;base64,${base64}`), 'data_url')
+    t.is(classifyInput(path.join(__dirname, 'missing.jpg')), 'unknown')
+    t.is(decode(base64).inputKind, 'base64')
+})
+
+test('decode trust score', (t) => {
+    const strong = decode(encode('hello, world', { errorCorrection: 'H', margin: 2 }).toString('base64'), { trustScore: true })
+    const weak = decode(encode('12345678', { barcodeFormat: BarcodeFormat.ITF, width: 300, height: 100, margin: 10 }).toString('base64'), { trustScore: true })
+    t.is(strong.trust, 1)
+    t.true(weak.trust < strong.trust)
+})
+
+test('decode relax formats on failure', (t) => {
+    const ean13 = encode('123456789012', { barcodeFormat: BarcodeFormat.Ean13, width: 380, height: 100, margin: 10 }).toString('base64')
+    t.is(decode(ean13, { barcodeFormat: [BarcodeFormat.QrCode] }), null)
+    const result = decode(ean13, { barcodeFormat: [BarcodeFormat.QrCode], relaxFormatsOnFailure: true })
+    t.is(result.text, '1234567890128')
+    t.true(result.formatRelaxed)
+})
+
+test('decode with custom binarizer', (t) => {
+    const threshold = (luma, width, height) => {
+        const stride = Math.ceil(width / 8)
+        const bitmap = Buffer.alloc(stride * height)
+        for (let y = 0; y < height; y++) {
+            for (let x = 0; x < width; x++) {
+                if (luma[y * width + x] < 128) {
+                    bitmap[y * stride + (x >> 3)] |= 0x80 >> (x & 7)
+                }
+            }
+        }
+        return bitmap
+    }
+    t.is(decodeWithBinarizer(path.join(__dirname, 'qrcode.jpg'), threshold).text, 'hello, world')
+
+    for (const options of [{ parallelStripes: 4 }, { candidateRegions: true }, { autoInvertPerRegion: true, decodeMulti: true }]) {
+        const [option] = Object.keys(options)
+        t.throws(() => decodeWithBinarizer(path.join(__dirname, 'qrcode.jpg'), threshold, options), { code: 'InvalidArg', message: new RegExp(option) })
+    }
+})
+
+test('decode with failing binarizer falls back to the default', (t) => {
+    const result = decodeWithBinarizer(path.join(__dirname, 'qrcode.jpg'), () => { throw new Error('model unavailable') })
+    t.is(result.text, 'hello, world')
+})
+
+test('video scanner', (t) => {
+    const frame = path.join(__dirname, 'qrcode.jpg')
+    const empty = encode('empty', { knockout: [{ x: 0, y: 0, width: 200, height: 200 }] }).toString('base64')
+    const scanner = new VideoScanner({ window: 2 })
+
+    t.deepEqual(scanner.pushFrame(frame), [])
+    t.deepEqual(scanner.pushFrame(frame).map((result) => result.text), ['hello, world'])
+    t.is(scanner.pushFrame(empty).length, 1)
+    t.is(scanner.pushFrame(empty).length, 0)
+
+    // A frame missing the code starts the count over
+    const gapped = new VideoScanner({ window: 5 })
+    t.deepEqual(gapped.pushFrame(frame), [])
+    t.deepEqual(gapped.pushFrame(empty), [])
+    t.deepEqual(gapped.pushFrame(frame), [])
+    t.deepEqual(gapped.pushFrame(frame).map((result) => result.text), ['hello, world'])
+    t.is(gapped.pushFrame(empty).length, 1)
+})
+
+test('decode normalized crop', (t) => {
+    const qrcode = path.join(__dirname, 'qrcode.jpg')
+    const full = decode(qrcode, { estimateModuleSize: true })
+    const result = decode(qrcode, { estimateModuleSize: true, cropNormalized: { x: 0.02, y: 0.02, width: 0.96, height: 0.96 } })
+    t.is(result.text, 'hello, world')
+    t.true(Math.abs(result.moduleSizePx - full.moduleSizePx) < 0.5)
+    t.throws(() => decode(qrcode, { cropNormalized: { x: 0, y: 0, width: 1.5, height: 1 } }), { code: 'InvalidArg' })
+    const crop = { x: 0, y: 0, width: 100, height: 100 }
+    t.throws(() => decode(qrcode, { crop, cropNormalized: { x: 0, y: 0, width: 1, height: 1 } }), { code: 'InvalidArg' })
+})
+
+test('decode detection info on failure', (t) => {
+    const occluded = encode('hello, world', { width: 200, height: 200, knockout: [{ x: 0, y: 0, width: 60, height: 60 }] }).toString('base64')
+    t.is(decode(occluded), null)
+    t.deepEqual(decode(occluded, { detectionInfo: true }), {
+        finderPatternsFound: 2,
+        qrCodeLocated: false,
+        dataMatrixLocated: false,
+        aztecLocated: false,
+    })
+    t.is(decode(path.join(__dirname, 'qrcode.jpg'), { detectionInfo: true }).text, 'hello, world')
+})
+
+test('decode restricted metadata keys', (t) => {
+    const qrcode = path.join(__dirname, 'qrcode.jpg')
+    t.is(decode(qrcode).metadata.SYMBOLOGY_IDENTIFIER, ']Q1')
+    t.deepEqual(decode(qrcode, { metadataKeys: ['ERROR_CORRECTION_LEVEL'] }).metadata, { ERROR_CORRECTION_LEVEL: '1' })
+})
+
+test('verify sheet', (t) => {
+    const sheet = path.join(__dirname, 'qrcode.jpg')
+    const report = verifySheet(sheet, ['hello, world', 'missing', 'hello, world'])
+    t.deepEqual(report.matched, ['hello, world'])
+    t.deepEqual(report.missing, ['missing', 'hello, world'])
+    t.deepEqual(report.extra, [])
+    t.deepEqual(verifySheet(sheet, []).extra.map((result) => result.text), ['hello, world'])
+})
+
+test('decode text only', (t) => {
+    const qrcode = path.join(__dirname, 'qrcode.jpg')
+    t.is(decodeText(qrcode), 'hello, world')
+    t.is(decodeText(qrcode, { decodeMulti: true }), 'hello, world')
+    t.is(decodeText(path.join(__dirname, 'missing.jpg')), null)
+})
+
+test('decode strip bom', (t) => {
+    const withBom = encode('\uFEFFhello').toString('base64')
+    t.is(decode(withBom).text, '\uFEFFhello')
+    const result = decode(withBom, { stripBom: true })
+    t.is(result.text, 'hello')
+    t.true(result.rawBytes.length > 0)
+    t.is(decodeText(withBom, { stripBom: true }), 'hello')
+})
+
+test('decode outline', (t) => {
+    const round = (outline) => outline.map(({ x, y }) => [Math.round(x), Math.round(y)])
+
+    const qrcode = encode('hello', { width: 300, margin: 0 }).toString('base64')
+    t.deepEqual(round(decode(qrcode).outline), [[3, 3], [297, 3], [297, 297], [3, 297]])
+
+    const code128 = encode('hello', { barcodeFormat: BarcodeFormat.Code128, width: 300, height: 120, margin: 10 }).toString('base64')
+    const outline = round(decode(code128).outline)
+    t.is(outline.length, 4)
+    t.true(outline[0][0] < outline[1][0] && outline[1][1] < outline[2][1])
+})
+
+test('decode point coordinates', (t) => {
+    const qrcode = encode('hello', { width: 300, margin: 0 }).toString('base64')
+    t.is(decode(qrcode).resultPoints.length, 3)
+
+    const normalized = decode(qrcode, { pointCoordinates: 'normalized' }).outline
+    t.deepEqual(normalized.map(({ x, y }) => [x, y]), [[0.01, 0.01], [0.99, 0.01], [0.99, 0.99], [0.01, 0.99]])
+
+    const [corner] = decode(qrcode, { pointCoordinates: 'both' }).outline
+    t.deepEqual([Math.round(corner.x), Math.round(corner.y), corner.nx, corner.ny], [3, 3, 0.01, 0.01])
+
+    // Rejected before the input is even loaded
+    t.throws(() => decode(qrcode, { pointCoordinates: 'inches' }), { code: 'InvalidArg', message: /pointCoordinates/ })
+    t.throws(() => decode(path.join(__dirname, 'missing.png'), { pointCoordinates: 'inches' }), { code: 'InvalidArg' })
+})
+
+// An EAN-8 code with 3 pixel modules centered on `center`, turned counterclockwise by `degrees`
+const ean8Image = (digits, { degrees = 0, width = 300, height = 300, center = [width / 2, height / 2] } = {}) => {
+    const codes = ['0001101', '0011001', '0010011', '0111101', '0100011', '0110001', '0101111', '0111011', '0110111', '0001011']
+    const right = (digit) => codes[digit].replace(/./g, (bit) => (bit === '0' ? '1' : '0'))
+    const modules = `101${[...digits.slice(0, 4)].map((digit) => codes[digit]).join('')}01010${[...digits.slice(4)].map(right).join('')}101`
+    const [cos, sin] = [Math.cos((degrees * Math.PI) / 180), Math.sin((degrees * Math.PI) / 180)]
+
+    return bmp(width, height, (x, y) => {
+        const [dx, dy] = [x - center[0], y - center[1]]
+        const [u, v] = [cos * dx - sin * dy, sin * dx + cos * dy]
+        return modules[Math.floor(u / 3 + modules.length / 2)] === '1' && Math.abs(v) < 40 ? [0, 0, 0] : [255, 255, 255]
+    })
+}
+
+test('decode rotate degrees', (t) => {
+    const skewed = ean8Image('96385074', { degrees: 30 }).toString('base64')
+    const options = { barcodeFormat: [BarcodeFormat.Ean8] }
+    t.is(decode(skewed, options), null)
+
+    const result = decode(skewed, { ...options, rotateDegrees: 30 })
+    t.is(result.text, '96385074')
+    // The ends of the code, in the skewed image
+    const [start, end] = result.resultPoints.map(({ x, y }) => [Math.round(x), Math.round(y)])
+    t.true(Math.abs(start[0] - 67) <= 3 && Math.abs(start[1] - 198) <= 3)
+    t.true(Math.abs(end[0] - 233) <= 3 && Math.abs(end[1] - 102) <= 3)
+})
+
+test('decode rotate degrees must be finite', (t) => {
+    const input = path.join(__dirname, 'qrcode.jpg')
+    t.throws(() => decode(input, { rotateDegrees: NaN }), { code: 'InvalidArg', message: /rotateDegrees/ })
+    t.throws(() => decode(input, { rotateDegrees: Infinity }), { code: 'InvalidArg', message: /rotateDegrees/ })
+    t.throws(() => decode(input, { rotateDegrees: -Infinity }), { code: 'InvalidArg', message: /rotateDegrees/ })
+})
+
+test('decode gs1 element strings', (t) => {
+    const options = { barcodeFormat: BarcodeFormat.DataMatrix, fnc1Placeholder: '|' }
+    const code = encode('010950110102091717250600|3012|3103000750|10ABC123', options).toString('base64')
+    t.deepEqual(decode(code, { parseGs1: true }).gs1, { '01': '09501101020917', 17: '250600', 30: '12', 3103: '000750', 10: 'ABC123' })
+    t.deepEqual(decode(code, { parseGs1: true, normalizeGs1Values: true }).gs1, { '01': '09501101020917', 17: '2025-06', 30: 12, 3103: 0.75, 10: 'ABC123' })
+    t.is(decode(code).gs1, undefined)
+    t.is(decode(encode('0109501101020917').toString('base64'), { parseGs1: true }).gs1, undefined)
+})
+
+test('decode gs1-128 element strings', (t) => {
+    const options = { barcodeFormat: BarcodeFormat.Code128, width: 400, height: 100, margin: 20 }
+    const code = encode('ñ0109501101020917ñ10ABC123ñ2110', options).toString('base64')
+    const result = decode(code, { parseGs1: true })
+    t.is(result.text, ']C10109501101020917\u001d10ABC123\u001d2110')
+    t.deepEqual(result.gs1, { '01': '09501101020917', 10: 'ABC123', 21: '10' })
+    t.deepEqual(decode(code, { parseGs1: true, assumeGs1: true }).gs1, result.gs1)
+    t.is(decode(code).gs1, undefined)
+})
+
+test('decode raw metadata', (t) => {
+    const pdf417 = encode('hello, world', { barcodeFormat: BarcodeFormat.Pdf417, width: 400, height: 200 }).toString('base64')
+    const result = decode(pdf417, { metadataRaw: true })
+    t.is(result.text, 'hello, world')
+    // Only Macro PDF417 symbols, split over several codes, have a control block
+    t.is(result.pdf417Metadata, undefined)
+    t.is(result.metadata.SYMBOLOGY_IDENTIFIER, decode(pdf417).metadata.SYMBOLOGY_IDENTIFIER)
+})
+
+test('decode code 128 function codes', (t) => {
+    // ñ, ò, ó and ô stand for FNC1 to FNC4 when encoding Code 128
+    const options = { barcodeFormat: BarcodeFormat.Code128, width: 400, height: 120 }
+    const gs1 = encode('ñ0112345678ñab', options).toString('base64')
+    t.deepEqual(decode(gs1, { returnFncCodes: true }).fnc, [{ position: 0, type: 1 }, { position: 10, type: 1 }])
+    const converted = decode(gs1, { returnFncCodes: true, assumeGs1: true })
+    t.is(converted.text, ']C10112345678\x1dab')
+    t.deepEqual(converted.fnc, [{ position: 0, type: 1 }, { position: 13, type: 1 }])
+
+    const result = decode(encode('abòcódôe', options).toString('base64'), { returnFncCodes: true })
+    t.is(result.text, 'abcd\xe5')
+    t.deepEqual(result.fnc, [{ position: 2, type: 2 }, { position: 3, type: 3 }, { position: 4, type: 4 }])
+    t.is(decode(encode('hello').toString('base64'), { returnFncCodes: true }).fnc, undefined)
+})
+
+test('decode pure grid', (t) => {
+    const qrCode = encode('hello, world', { width: 200, height: 200 }).toString('base64')
+    t.is(decode(qrCode, { pureBarcode: true, pureGrid: { rows: 21, cols: 21 } }).text, 'hello, world')
+    t.is(decode(qrCode, { pureBarcode: true, pureGrid: { rows: 25, cols: 25 } }), null)
+    t.throws(() => decode(qrCode, { pureGrid: { rows: 21, cols: 21 } }), { code: 'InvalidArg' })
+
+    const dataMatrix = encode('hello, world', { barcodeFormat: BarcodeFormat.DataMatrix, width: 200, height: 200 }).toString('base64')
+    const result = decode(dataMatrix, { pureBarcode: true, pureGrid: { rows: 8, cols: 32 } })
+    t.is(result.text, 'hello, world')
+    t.is(result.format, BarcodeFormat.DataMatrix)
+})
+
+test('decode mixed polarities per region', (t) => {
+    const paper = encodeWithMetadata('paper', { includeMatrix: true }).matrix
+    const screen = encodeWithMetadata('screen', { includeMatrix: true }).matrix
+    // A dark on light code on the left half, a light on dark one on the right half, at 8 pixels per module
+    const mixed = bmp(500, 300, (x, y) => {
+        const [matrix, left] = x < 250 ? [paper, 41] : [screen, 291]
+        const [column, row] = [Math.floor((x - left) / 8), Math.floor((y - 66) / 8)]
+        const inside = x >= left && y >= 66 && column < matrix.width && row < matrix.height
+        const dark = inside ? matrix.data[row * matrix.width + column] : false
+        return dark === (x < 250) ? [0, 0, 0] : [255, 255, 255]
+    }).toString('base64')
+
+    t.deepEqual(decode(mixed, { decodeMulti: true }).map(({ text }) => text), ['paper'])
+    const results = decode(mixed, { decodeMulti: true, autoInvertPerRegion: true })
+    t.deepEqual(results.map(({ text }) => text).sort(), ['paper', 'screen'])
+})
+
+test('decode candidate regions', (t) => {
+    const sparse = ean8Image('96385074', { width: 1200, height: 900, center: [850, 650] }).toString('base64')
+    const result = decode(sparse, { candidateRegions: true })
+    t.is(result.text, '96385074')
+    const [start, end] = result.resultPoints
+    t.true(Math.abs(start.x - 754) <= 3 && Math.abs(end.x - 946) <= 3)
+    t.true(Math.abs(start.y - 650) < 40 && Math.abs(end.y - 650) < 40)
+
+    t.is(decode(bmp(800, 600, () => [255, 255, 255]).toString('base64'), { candidateRegions: true }), null)
+})
+
+test('decode attempt timeline', (t) => {
+    const base64 = encode('hello, world').toString('base64')
+    t.is(decode(base64).attempts, undefined)
+    t.deepEqual(decode(base64, { attemptTimeline: true }).attempts.map(({ stage, found }) => [stage, found]), [['load', false], ['decode', true]])
+
+    const blank = bmp(100, 100, () => [255, 255, 255]).toString('base64')
+    const info = decode(blank, { attemptTimeline: true, alsoInverted: true, detectionInfo: true })
+    t.deepEqual(info.attempts.map(({ stage }) => stage), ['load', 'decode', 'decode inverted', 'detection info'])
+    t.true(info.attempts.every(({ ms, found }) => ms >= 0 && !found))
+})
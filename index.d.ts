@@ -3,6 +3,12 @@
 
 /* auto-generated by NAPI-RS */
 
+/**
+ * Barcode formats supported by rxing.
+ *
+ * The numeric value of each variant is part of the public API (see `DecodeResult.formatCode`)
+ * and must stay stable across releases: new formats are only ever appended.
+ */
 export const enum BarcodeFormat {
   /** Aztec 2D barcode format.  */
   AZTEC = 0,
@@ -38,9 +44,16 @@ export const enum BarcodeFormat {
   UpcE = 15,
   /** UPC/EAN extension format. Not a stand-alone format.  */
   UpcEanExtension = 16,
-  /** */
+  /** Unsupported format.  */
   UnsupportedFormat = 17
 }
+/** A rectangular region of an image, in pixels */
+export interface Rect {
+  x: number
+  y: number
+  width: number
+  height: number
+}
 export interface EncodeOptions {
   barcodeFormat?: BarcodeFormat
   width?: number
@@ -61,6 +74,108 @@ export interface EncodeOptions {
   forceC40?: boolean
   code128Compact?: boolean
   outputFile?: string
+  /** Pixel regions to leave blank in the final image, e.g. for variable data printed later */
+  knockout?: Array<Rect>
+  /** Decode the rendered image and only return it if it reads back as `data` */
+  verify?: boolean
+  /**
+   * Decode a copy of the rendered image degraded as by printing (dot gain, blur and noise, in proportion to the
+   * module size), and emit a warning if it no longer reads back as `data`. The image is returned either way, and
+   * `encodeWithMetadata` reports the outcome as `degradationPassed`
+   */
+  simulateDegradation?: boolean
+  /**
+   * Output the code flush to its edges, without any quiet zone, overriding `margin`.
+   * The result will not scan on its own, which is warned about for formats that require a quiet zone: it is meant
+   * to be composited into a design that supplies its own quiet zone
+   */
+  noQuietZone?: boolean
+  /**
+   * Pick the smallest margin that still decodes, overriding `margin`: starting from the quiet zone required by the
+   * format's specification, or from none if `allowNarrowQuietZone` is set, the margin is grown until the rendered
+   * image reads back as `data`, up to three times the specified quiet zone. Encoding fails if that doesn't decode
+   * either, and when `noQuietZone` is set. Use `encodeWithMetadata` to get the chosen margin
+   */
+  autoMargin?: boolean
+  /** Let `autoMargin` try margins narrower than the quiet zone required by the format's specification */
+  allowNarrowQuietZone?: boolean
+  /**
+   * Make `encodeWithMetadata` also return the module matrix of the code, as `matrix`, to render it again later
+   * without encoding the data a second time
+   */
+  includeMatrix?: boolean
+  /** Image format of the output, either `"jpeg"` (the default) or `"png"` */
+  outputFormat?: string
+  /**
+   * Store the encoded data and barcode format in the image, as `Barcode Data` and `Barcode Format` PNG text
+   * chunks. This always produces a PNG, whatever `outputFormat` is set to
+   */
+  embedPayloadMetadata?: boolean
+  /**
+   * Foreground colors, as `#rrggbb` hex strings, cycled through for each row of modules. Every color must be
+   * darker than the white background with a contrast ratio of at least 3:1, or encoding fails unless
+   * `allowLowContrast` is set, and the result is decoded to check it still scans
+   */
+  rowColors?: Array<string>
+  /**
+   * An image (PNG, JPEG, ...) scaled to cover the whole code and used as its background, with the modules drawn
+   * over it. Encoding fails when more than 5% of the background lacks a 3:1 contrast ratio with the modules,
+   * unless `allowLowContrast` is set, and the result is decoded to check it still scans
+   */
+  backgroundImage?: Buffer
+  /**
+   * How to handle an odd number of digits for ITF, which can only encode digit pairs: `"none"` (the default)
+   * fails with a warning, `"leading-zero"` prepends a zero. Other values fail with a warning, whatever the digit count
+   */
+  itfPadding?: 'none' | 'leading-zero'
+  /**
+   * Color of the three finder patterns of a QR code, as a `#rrggbb` hex string, the other modules keeping their
+   * color. Like `rowColors` it must have a contrast ratio of at least 3:1 with white. The result is decoded to
+   * check it still scans, and encoding fails for other formats
+   */
+  finderColor?: string
+  /**
+   * Opacity of the modules, from 0 to 1, for faint watermark-like codes: the modules are blended with the white
+   * background or `backgroundImage` under them. Like `rowColors`, the lightest modules must keep a contrast ratio
+   * of at least 3:1 with white, or encoding fails unless `allowLowContrast` is set. The result is always decoded to
+   * check it still scans
+   */
+  moduleOpacity?: number
+  /**
+   * A character standing for FNC1 in `data`, for GS1 Data Matrix codes with several application identifiers,
+   * e.g. `"|"` to encode `"0109501101020917|10ABC"`. Every occurrence is encoded as an FNC1 separator, which
+   * decodes as a GS character (`\x1D`), and a leading FNC1 is always added, so a placeholder at the start of the
+   * data is optional. This uses a simpler encoder, ignoring `dataMatrixCompact`, `forceC40` and `gs1Format`, and
+   * encoding fails for other formats
+   */
+  fnc1Placeholder?: string
+  /**
+   * Render colors that are unlikely to scan (`rowColors`, `finderColor` or a `backgroundImage` with less than a
+   * 3:1 contrast ratio) with a warning instead of failing. This also skips decoding `rowColors`, `finderColor`
+   * and `backgroundImage` results to check they scan, unless `verify` is set
+   */
+  allowLowContrast?: boolean
+}
+/** An encoded barcode, along with details about how it was encoded */
+export interface EncodeResult {
+  /** The encoded image, as `encode` returns it */
+  image: Buffer
+  /** The margin the code was encoded with, which `autoMargin` may have picked */
+  margin: number
+  /**
+   * The module matrix of the code, if `includeMatrix` is set: one cell per module, without scaling or quiet zone,
+   * e.g. 21x21 for a version 1 QR code. Linear codes are a single row of bars and spaces, and PDF417 codes a row
+   * per row of codewords. Image level options such as `rowColors` or `knockout` are left out
+   */
+  matrix?: CodeMatrix
+  /** Whether the degraded copy still read back as `data`, if `simulateDegradation` is set */
+  degradationPassed?: boolean
+}
+/** The modules of an encoded code, row by row, `true` being dark */
+export interface CodeMatrix {
+  width: number
+  height: number
+  data: Array<boolean>
 }
 /**
  * Encode a barcode from a string, returning a buffer representing the image
@@ -79,6 +194,125 @@ export interface EncodeOptions {
  * fs.writeFileSync('hello-world.png', buffer);
 */
 export function encode(data: string, options?: EncodeOptions | undefined | null): Buffer | null
+/**
+ * Encode a barcode from a string like `encode`, letting a callback change its modules before they are rendered
+ *
+ * The callback is called with the module matrix of the code, one cell per module without any quiet zone (a single
+ * row for linear codes), and must return a matrix of the same size, e.g. to draw a custom marker. The returned
+ * modules are then scaled and padded to the requested size like the original ones. It is called again for every
+ * margin `autoMargin` tries. The result is decoded to check it still scans, and encoding fails with a warning if it
+ * doesn't.
+ *
+ * @param {string} data The data to encode
+ * @param {(matrix: CodeMatrix) => CodeMatrix} transform The callback changing the matrix
+ * @param {EncodeOptions} [options] Optional options to pass to the encoder
+ *
+ * @returns {Buffer|null} A buffer representing the encoded barcode, or `null` if the barcode could not be encoded or encountered an error
+ *
+ * @example
+ * const { encodeWithTransform } = require('@rxing/rxing');
+ *
+ * const buffer = encodeWithTransform('Hello World!', ({ width, height, data }) => {
+ *   // Flip the center module, which error correction recovers from
+ *   const center = Math.floor(height / 2) * width + Math.floor(width / 2);
+ *   data[center] = !data[center];
+ *   return { width, height, data };
+ * });
+*/
+export function encodeWithTransform(data: string, transform: (matrix: CodeMatrix) => CodeMatrix, options?: EncodeOptions | undefined | null): Buffer | null
+/**
+ * Encode a barcode from a string like `encode`, also returning details about how it was encoded
+ *
+ * @param {string} data The data to encode
+ * @param {EncodeOptions} [options] Optional options to pass to the encoder
+ *
+ * @returns {EncodeResult|null} The encoded image and its details, or `null` if the barcode could not be encoded or encountered an error
+ *
+ * @example
+ * const { encodeWithMetadata } = require('@rxing/rxing');
+ *
+ * const { image, margin } = encodeWithMetadata('Hello World!', { autoMargin: true });
+*/
+export function encodeWithMetadata(data: string, options?: EncodeOptions | undefined | null): EncodeResult | null
+/**
+ * Estimate the size in bytes of the image `encode` would return for the same arguments, without rendering it.
+ * This is a planning figure rather than an exact size: it models the output of a plain black and white code, so
+ * options styling the image, like `rowColors` or `backgroundImage`, are not accounted for
+ *
+ * @param {string} data The data to encode
+ * @param {EncodeOptions} [options] Optional options to pass to the encoder
+ *
+ * @returns {number|null} The estimated size in bytes, or `null` if the barcode could not be encoded
+ *
+ * @example
+ * const { estimateEncodedSize } = require('@rxing/rxing');
+ *
+ * const bytes = estimateEncodedSize('Hello World!', { width: 400, outputFormat: 'png' });
+*/
+export function estimateEncodedSize(data: string, options?: EncodeOptions | undefined | null): number | null
+export interface PdfBatchItem {
+  /** The data to encode */
+  data: string
+  /** Optional label printed below the code */
+  caption?: string
+}
+export interface PdfBatchOptions {
+  /** Options used to encode every item, `width` and `height` set the raster resolution of each code */
+  encode?: EncodeOptions
+  /** Page width in millimeters, defaults to A4 (210) */
+  pageWidthMm?: number
+  /** Page height in millimeters, defaults to A4 (297) */
+  pageHeightMm?: number
+  /** Printed code width in millimeters, defaults to 50 */
+  codeWidthMm?: number
+  /** Printed code height in millimeters, defaults to keeping the aspect ratio of the encoded image */
+  codeHeightMm?: number
+}
+/**
+ * Encode a batch of barcodes into a single PDF document, one code per page
+ *
+ * @param {Array<PdfBatchItem>} items The data, and optional caption, of each code
+ * @param {PdfBatchOptions} [options] Optional encoding and page layout options
+ *
+ * @returns {Buffer|null} A buffer containing the PDF, or `null` if any of the barcodes could not be encoded
+ *
+ * @example
+ * const { encodePdfBatch } = require('@rxing/rxing');
+ * const fs = require('fs');
+ *
+ * const items = [{ data: 'VOUCHER-1', caption: 'Voucher #1' }, { data: 'VOUCHER-2', caption: 'Voucher #2' }];
+ * fs.writeFileSync('vouchers.pdf', encodePdfBatch(items, { codeWidthMm: 40 }));
+*/
+export function encodePdfBatch(items: Array<PdfBatchItem>, options?: PdfBatchOptions | undefined | null): Buffer | null
+/** A contact sheet of the same data encoded in every format that can hold it */
+export interface PreviewSheet {
+  /** The sheet image, in `outputFormat` */
+  image: Buffer
+  /** The formats on the sheet, in the order of their tiles, left to right then top to bottom */
+  formats: Array<BarcodeFormat>
+  /** The formats left off the sheet because they can't encode the data, such as EAN-13 for text */
+  skipped: Array<BarcodeFormat>
+}
+/**
+ * Encode the same data in every format rxing can write, and tile the codes into a single image with the name of
+ * their format below each
+ *
+ * The tiles are laid out in a grid as close to square as possible, in the order of `BarcodeFormat`. Formats that
+ * can't encode the data are left off the sheet and listed in `skipped`.
+ *
+ * @param {string} data The data to encode
+ * @param {EncodeOptions} [options] Optional options to pass to the encoder for every code, `barcodeFormat` is ignored
+ *
+ * @returns {PreviewSheet|null} The sheet and the formats on it, or `null` if no format could encode the data
+ *
+ * @example
+ * const { encodePreviewSheet } = require('@rxing/rxing');
+ * const fs = require('fs');
+ *
+ * const { image, skipped } = encodePreviewSheet('12345670', { width: 160, height: 160, outputFormat: 'png' });
+ * fs.writeFileSync('formats.png', image);
+*/
+export function encodePreviewSheet(data: string, options?: EncodeOptions | undefined | null): PreviewSheet | null
 export interface DecodeOptions {
   tryHarder?: boolean
   decodeMulti?: boolean
@@ -92,12 +326,243 @@ export interface DecodeOptions {
   allowedEanExtensions?: Array<number>
   alsoInverted?: boolean
   other?: string
+  /** Estimate the size of a module, in pixels, and report it as `moduleSizePx` */
+  estimateModuleSize?: boolean
+  /** Estimate the scan resolution from the module size, reporting it as `estimatedDpi` */
+  estimateDpi?: EstimateDpiOptions
+  /**
+   * Split the image into this many horizontal stripes and search them in parallel, returning the first hit.
+   * Only applies to single (non `decodeMulti`) decodes of raster images. Stripes span the whole width of the
+   * image, so only codes running vertically can be split across them, and those are not reassembled: rxing only
+   * reports complete reads, with both guard patterns in view. Stripes are at least 40 rows tall, so short images are
+   * split into fewer
+   */
+  parallelStripes?: number
+  /**
+   * Number of rows shared by adjacent stripes when `parallelStripes` is set, defaults to one stripe height. Must be
+   * at least the height of the barcode, or it may be cut in half and missed, so pass it for codes taller than a stripe
+   */
+  stripeOverlap?: number
+  /** Compute a 0-1 score of how likely each result is to be a genuine read, reported as `trust` */
+  trustScore?: boolean
+  /**
+   * If decoding with `barcodeFormat` fails, retry once with every format and flag results with `formatRelaxed`.
+   * Failed decodes take about twice as long
+   */
+  relaxFormatsOnFailure?: boolean
+  /** Only search this region of the image, in pixels. Clamped to the image bounds, and must be at least 40 pixels wide and high */
+  crop?: Rect
+  /**
+   * Only search this region of the image, as fractions (0 to 1) of its width and height. Clamped to the image
+   * bounds, an `InvalidArg` error is thrown if any fraction is out of range or if `crop` is also set
+   */
+  cropNormalized?: NormalizedRect
+  /**
+   * Rotate the image clockwise by this many degrees, after cropping and before detection, to straighten a code
+   * skewed by a known angle. The corners exposed by the rotation are filled with the average brightness of the
+   * image, and the returned points are mapped back to the original image. Must be finite
+   */
+  rotateDegrees?: number
+  /** Return what the detectors found, instead of `null`, when no barcode could be decoded */
+  detectionInfo?: boolean
+  /** Only return these keys in `DecodeResult.metadata`, all of them are returned when unset */
+  metadataKeys?: Array<string>
+  /**
+   * Remove a leading byte order mark from `text`, as some encoders prepend one. `rawBytes` are left intact.
+   * Off by default, so that `text` is exactly what was encoded
+   */
+  stripBom?: boolean
+  /**
+   * Form of `resultPoints` and `outline`: `"pixel"` (the default), `"normalized"` for fractions (0 to 1) of the
+   * image width and height, or `"both"` to add the normalized coordinates as `nx` and `ny`. Points of SVG
+   * inputs, whose size isn't known, are always in pixels. Other values throw an `InvalidArg` error
+   */
+  pointCoordinates?: 'pixel' | 'normalized' | 'both'
+  /**
+   * Parse the data of GS1 codes (GS1-128, GS1 DataBar, GS1 Data Matrix and GS1 QR Code) into `gs1`, by
+   * application identifier (AI). Implies `assumeGs1`, so GS1-128 `text` keeps its separators
+   */
+  parseGs1?: boolean
+  /**
+   * With `parseGs1`, convert the values of well known AIs: dates (such as 11, production date, or 17, expiration
+   * date) to ISO 8601 strings, `YYYY-MM` when the day is left out, and counts (30 and 37) and measures (310n to
+   * 369n, 390n and 392n) to numbers. Other values, and values that aren't valid for their AI, stay strings
+   */
+  normalizeGs1Values?: boolean
+  /**
+   * Also return the structured metadata that `metadata` can't hold as strings, as objects such as
+   * `pdf417Metadata`
+   */
+  metadataRaw?: boolean
+  /**
+   * Only decode the regions of the image with sharp edges, found by a quick pre-pass, which is much faster on
+   * large images where codes take up little space. Codes with little contrast, blurred ones and ones with very
+   * large modules may not stand out and then go unnoticed, so it trades some accuracy for speed. Only applies to
+   * raster images decoded with the default binarizer
+   */
+  candidateRegions?: boolean
+  /**
+   * Record the stages of the decode in order, with their duration and whether they found a barcode, as `attempts`
+   * on the results, or on the `DetectionInfo` of failed decodes when `detectionInfo` is set. The stages are
+   * `load` (reading the image), `crop`, `rotate`, `binarize` (the custom binarizer), `decode`, `decode inverted`
+   * (the `alsoInverted` retry), `relaxed formats` (`relaxFormatsOnFailure`, also followed by an inverted retry)
+   * and `detection info`. The retries rxing makes on its own, such as rotating for 1D codes with `tryHarder`, are
+   * part of the stage they happen in
+   */
+  attemptTimeline?: boolean
+  /**
+   * Report the FNC1 to FNC4 function codes of Code 128 symbols as `fnc`. rxing leaves them out of `text`, apart
+   * from FNC1 with `assumeGs1`, even though they change how it should be read, such as FNC1 marking GS1 data
+   */
+  returnFncCodes?: boolean
+  /**
+   * With `pureBarcode`, skip detection and read the modules of a 2D code (QR Code, Data Matrix, Aztec, ...) of
+   * known size straight from the image, for setups that frame a single clean code. The dark pixels of the image,
+   * after `crop` and `rotateDegrees`, are taken to span exactly the `rows` by `cols` modules of the symbol, quiet
+   * zone excluded: the code must be upright, with square corners and evenly sized modules, and nothing else dark
+   * may be in view. Each module is read from the pixel at its center. An `InvalidArg` error is thrown if
+   * `pureBarcode` isn't set, and decoding fails for SVG files
+   */
+  pureGrid?: PureGrid
+  /**
+   * With `decodeMulti`, also decode every candidate region of the image (see `candidateRegions`) both as is and
+   * inverted, for scenes mixing dark on light and light on dark codes, such as a screen photographed next to
+   * paper, where `alsoInverted` only retries inverted where rxing found nothing and can miss some. The results are
+   * merged, dropping the codes found more than once. On top of the usual decode of the whole image, every region is
+   * decoded twice, in parallel, so expect decodes to take two to three times as long
+   */
+  autoInvertPerRegion?: boolean
+}
+/** A rectangular region of an image, as fractions of its width and height */
+export interface NormalizedRect {
+  x: number
+  y: number
+  width: number
+  height: number
+}
+/** The number of modules of a 2D code, for `pureGrid` */
+export interface PureGrid {
+  rows: number
+  cols: number
+}
+export interface EstimateDpiOptions {
+  /** The physical width of a single module (the X-dimension), in millimeters */
+  xDimensionMm: number
 }
 export interface DecodeResult {
   text: string
   rawBytes: Array<number>
   numBits: number
   format: BarcodeFormat
+  /** Stable numeric id of `format`, suitable for storage */
+  formatCode: number
+  /** Human readable name of `format`, as reported by rxing (e.g. `"qrcode"`) */
+  formatName: string
+  /** Estimated width of a module in pixels, if `estimateModuleSize` or `estimateDpi` is set */
+  moduleSizePx?: number
+  /** Estimated resolution of the source image in dots per inch, if `estimateDpi` is set */
+  estimatedDpi?: number
+  /** How the input was interpreted: `"file"`, `"data_url"`, `"base64"` or `"unknown"` */
+  inputKind: string
+  /**
+   * Confidence that the result is not a false positive, from 0 to 1, if `trustScore` is set.
+   *
+   * This is a weighted average of the signals that apply to the format:
+   * - 0.4: check digit, 1 for formats with a mandatory checksum or error correction, 0.5 otherwise
+   * - 0.2: module size, reaching 1 at 2px or more
+   * - 0.2: quiet zone, the fraction of light pixels in the 5 modules on either side of a 1D code
+   * - 0.2: decode margin, the error correction level of QR Code and PDF417 symbols
+   */
+  trust?: number
+  /** Set to `true` when the result was only found after `relaxFormatsOnFailure` dropped the format filter */
+  formatRelaxed?: boolean
+  /**
+   * Metadata reported by the reader, keyed by rxing's metadata type (e.g. `ORIENTATION`, `SYMBOLOGY_IDENTIFIER`).
+   * Numbers and booleans are formatted as strings and `BYTE_SEGMENTS` as comma separated base64 segments.
+   * Restricted to `metadataKeys` when set
+   */
+  metadata: Record<string, string>
+  /**
+   * Outline of the code as an ordered polygon, clockwise in image space and starting from the corner closest to
+   * the top left of the image. 2D codes are outlined from their finder or corner points and 1D codes with the four
+   * corners of their bars. Empty when the reader reported no points
+   */
+  outline: Array<ImagePoint>
+  /** The points reported by the reader, such as QR code finder pattern centers or the ends of a 1D scan line */
+  resultPoints: Array<ImagePoint>
+  /**
+   * The values of the GS1 element strings by AI, if `parseGs1` is set and the code holds GS1 data that could be
+   * parsed. Values are strings unless `normalizeGs1Values` converted them
+   */
+  gs1?: Record<string, string | number>
+  /** The Macro PDF417 control block of PDF417 symbols split over several codes, if `metadataRaw` is set */
+  pdf417Metadata?: Pdf417Metadata
+  /** The stages of the decode, if `attemptTimeline` is set */
+  attempts?: Array<Attempt>
+  /** The function codes of Code 128 symbols in the order they appear, if `returnFncCodes` is set */
+  fnc?: Array<FncCode>
+}
+/** A function code of a Code 128 symbol, as reported by `returnFncCodes` */
+export interface FncCode {
+  /**
+   * Index in `text` of the character the code precedes. With `assumeGs1`, FNC1 codes are in `text`, as the `]C1`
+   * prefix or a GS character, and this is the index where they start
+   */
+  position: number
+  /** Which function code it is, from 1 for FNC1 to 4 for FNC4 */
+  type: number
+}
+/**
+ * The Macro PDF417 control block, describing how a file is split over several PDF417 symbols.
+ * Optional fields are only set when the symbol carries them
+ */
+export interface Pdf417Metadata {
+  /** Index of this symbol among the segments of the file, from 0 */
+  segmentIndex: number
+  /** Identifier shared by all the segments of the file */
+  fileId: string
+  /** Whether this symbol is the last segment of the file */
+  lastSegment: boolean
+  segmentCount?: number
+  sender?: string
+  addressee?: string
+  fileName?: string
+  /** Size of the file in bytes */
+  fileSize?: number
+  /** Timestamp of the file, in seconds since the Unix epoch */
+  timestamp?: number
+  /** CRC-16 checksum of the file */
+  checksum?: number
+}
+/** A stage of a decode, as recorded by `attemptTimeline` */
+export interface Attempt {
+  stage: string
+  /** Duration of the stage, in milliseconds */
+  ms: number
+  /** Whether the stage found a barcode, always `false` for the stages preparing the image */
+  found: boolean
+}
+/** A point in an image, in pixels unless `pointCoordinates` is `"normalized"` */
+export interface ImagePoint {
+  x: number
+  y: number
+  /** `x` as a fraction of the image width, if `pointCoordinates` is `"both"` */
+  nx?: number
+  /** `y` as a fraction of the image height, if `pointCoordinates` is `"both"` */
+  ny?: number
+}
+/** What the detectors found in an image where no barcode could be decoded */
+export interface DetectionInfo {
+  /** Number of QR code finder patterns (the squares in three of its corners) found */
+  finderPatternsFound: number
+  /** Whether three finder patterns were found in a plausible arrangement for a QR code */
+  qrCodeLocated: boolean
+  /** Whether the outline of a Data Matrix code was found */
+  dataMatrixLocated: boolean
+  /** Whether the bullseye and orientation marks of an Aztec code were found */
+  aztecLocated: boolean
+  /** The stages of the decode, if `attemptTimeline` is set */
+  attempts?: Array<Attempt>
 }
 /**
  * Decode a barcode from a file or base64 string
@@ -105,7 +570,7 @@ export interface DecodeResult {
  * @param {string} input Either a path to a file or a base64 string
  * @param {DecodeOptions} [options] Optional options to pass to the decoder
  *
- * @returns {DecodeResult|Array<DecodeResult>|null} The decode result or a list of decode results if `options.decodeMulti` is set to `true`, or `null` if the barcode could not be decoded or encountered an error
+ * @returns {DecodeResult|Array<DecodeResult>|DetectionInfo|null} The decode result or a list of decode results if `options.decodeMulti` is set to `true`, or `null` if the barcode could not be decoded or encountered an error. When `options.detectionInfo` is set, a failed decode returns what the detectors found instead of `null`
  *
  * @example
  * const { decode } = require('@rxing/rxing');
@@ -115,4 +580,120 @@ export interface DecodeResult {
  * const result = decode('data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAA');
  * console.log(result.text);
 */
-export function decode(input: string, options?: DecodeOptions | undefined | null): DecodeResult | Array<DecodeResult> | null
+export function decode(input: string, options?: DecodeOptions | undefined | null): DecodeResult | Array<DecodeResult> | DetectionInfo | null
+/**
+ * Decode a barcode from a file or base64 string, using a custom binarizer
+ *
+ * The binarizer is called once, with the grayscale pixels of the image (one byte per pixel, row by row), and must
+ * return a 1 bit per pixel bitmap: rows are padded to whole bytes, the most significant bit comes first, and a set
+ * bit is dark. If it throws or returns anything else, rxing's default binarizer is used instead. Derived images
+ * (rotated for `tryHarder`, or inverted for `alsoInverted`) always use the default binarizer, and `parallelStripes`,
+ * `candidateRegions` and `autoInvertPerRegion`, which search parts of the image on their own, can't be combined with
+ * it: they throw an `InvalidArg` error.
+ *
+ * @param {string} input Either a path to a file or a base64 string
+ * @param {(luma: Buffer, width: number, height: number) => Buffer} binarizer The binarizer callback
+ * @param {DecodeOptions} [options] Optional options to pass to the decoder
+ *
+ * @returns {DecodeResult|Array<DecodeResult>|DetectionInfo|null} The decode result or a list of decode results if `options.decodeMulti` is set to `true`, or `null` if the barcode could not be decoded or encountered an error. When `options.detectionInfo` is set, a failed decode returns what the detectors found instead of `null`
+ *
+ * @example
+ * const { decodeWithBinarizer } = require('@rxing/rxing');
+ * const result = decodeWithBinarizer('path/to/file.png', (luma, width, height) => myModel.binarize(luma, width, height));
+ * console.log(result.text);
+*/
+export function decodeWithBinarizer(input: string, binarizer: (luma: Buffer, width: number, height: number) => Buffer, options?: DecodeOptions | undefined | null): DecodeResult | Array<DecodeResult> | DetectionInfo | null
+/**
+ * Decode a barcode from a file or base64 string, returning only its text
+ *
+ * This skips building the full result, for loops where nothing else is needed. Options that only add fields to the
+ * result (such as `trustScore` or `estimateModuleSize`) have no effect, and `decodeMulti` is ignored: only the text
+ * of the first barcode found is returned.
+ *
+ * @param {string} input Either a path to a file or a base64 string
+ * @param {DecodeOptions} [options] Optional options to pass to the decoder
+ *
+ * @returns {string|null} The decoded text, or `null` if the barcode could not be decoded or encountered an error
+ *
+ * @example
+ * const { decodeText } = require('@rxing/rxing');
+ * const text = decodeText('path/to/file.png');
+ * console.log(text);
+*/
+export function decodeText(input: string, options?: DecodeOptions | undefined | null): string | null
+export interface SheetReport {
+  /** Expected payloads that were found, in the order of `expected` */
+  matched: Array<string>
+  /** Expected payloads that were not found, in the order of `expected` */
+  missing: Array<string>
+  /** Codes found on the sheet that weren't expected, or that were found more times than expected */
+  extra: Array<DecodeResult>
+}
+/**
+ * Decode every barcode on a sheet and compare them against the payloads expected to be printed on it
+ *
+ * Payloads expected several times must be found as many times to all be matched.
+ *
+ * @param {string} input Either a path to a file or a base64 string
+ * @param {Array<string>} expected The payloads expected on the sheet
+ * @param {DecodeOptions} [options] Optional options to pass to the decoder, `decodeMulti` is always enabled
+ *
+ * @returns {SheetReport} The matched and missing payloads, and the unexpected codes. Every payload is missing if the input could not be read
+ *
+ * @example
+ * const { verifySheet } = require('@rxing/rxing');
+ * const report = verifySheet('path/to/sheet.png', ['LABEL-1', 'LABEL-2', 'LABEL-3']);
+ * console.log(report.missing);
+*/
+export function verifySheet(input: string, expected: Array<string>, options?: DecodeOptions | undefined | null): SheetReport
+export interface VideoScannerOptions {
+  /** Options used to decode every frame, `decodeMulti` and `trustScore` are always enabled */
+  decode?: DecodeOptions
+  /** Number of frames a payload is remembered for after it was last seen, defaults to 10 */
+  window?: number
+  /**
+   * Number of consecutive frames a payload must be seen in before it is reported, defaults to 2. A frame missing
+   * it starts the count over, once reported it stays so until it leaves the window
+   */
+  minFrames?: number
+}
+/**
+ * Report how `decode` would interpret an input, without decoding it
+ *
+ * @param {string} input Either a path to a file or a base64 string
+ *
+ * @returns {string} `"data_url"` or `"base64"` if the input parses as such, `"file"` if it names an existing file,
+ * and `"unknown"` otherwise (it will still be treated as a file path)
+ *
+ * @example
+ * const { classifyInput } = require('@rxing/rxing');
+ * console.log(classifyInput('path/to/file.png')); // "file"
+*/
+export function classifyInput(input: string): string
+/**
+ * Aggregates detections across the frames of a live video
+ *
+ * Reads are deduplicated by format and text, keeping the one with the highest `trust` of each payload until it has
+ * not been seen for `window` frames.
+ *
+ * @example
+ * const { VideoScanner } = require('@rxing/rxing');
+ * const scanner = new VideoScanner({ window: 15 });
+ * for (const frame of frames) {
+ *   const stable = scanner.pushFrame(frame);
+ *   console.log(stable.map((result) => result.text));
+ * }
+ */
+export class VideoScanner {
+  constructor(options?: VideoScannerOptions | undefined | null)
+  /**
+   * Decode a frame and return the stable set of reads, sorted by decreasing `trust`
+   *
+   * @param {string} input Either a path to a file or a base64 string
+   *
+   * @returns {Array<DecodeResult>} The best read of every payload seen in at least `minFrames` consecutive frames, and within the last `window` frames
+   */
+  pushFrame(input: string): Array<DecodeResult>
+  /** Forget every tracked read */
+  reset(): void
+}
@@ -246,8 +246,18 @@ if (!nativeBinding) {
   throw new Error(`Failed to load native binding`)
 }
 
-const { BarcodeFormat, encode, decode } = nativeBinding
+const { BarcodeFormat, encode, encodeWithTransform, encodeWithMetadata, estimateEncodedSize, encodePdfBatch, encodePreviewSheet, decode, decodeWithBinarizer, decodeText, verifySheet, VideoScanner, classifyInput } = nativeBinding
 
 module.exports.BarcodeFormat = BarcodeFormat
 module.exports.encode = encode
+module.exports.encodeWithTransform = encodeWithTransform
+module.exports.encodeWithMetadata = encodeWithMetadata
+module.exports.estimateEncodedSize = estimateEncodedSize
+module.exports.encodePdfBatch = encodePdfBatch
+module.exports.encodePreviewSheet = encodePreviewSheet
 module.exports.decode = decode
+module.exports.decodeWithBinarizer = decodeWithBinarizer
+module.exports.decodeText = decodeText
+module.exports.verifySheet = verifySheet
+module.exports.VideoScanner = VideoScanner
+module.exports.classifyInput = classifyInput
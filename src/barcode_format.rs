@@ -3,62 +3,94 @@
 use napi::bindgen_prelude::*;
 use rxing::BarcodeFormat;
 
+/**
+ * Barcode formats supported by rxing.
+ *
+ * The numeric value of each variant is part of the public API (see `DecodeResult.formatCode`)
+ * and must stay stable across releases: new formats are only ever appended.
+ */
 #[napi(js_name = "BarcodeFormat")]
 #[derive(Debug, PartialEq, Eq, Hash)]
 pub enum JsBarcodeFormat {
     /** Aztec 2D barcode format. */
-    AZTEC,
+    AZTEC = 0,
 
     /** CODABAR 1D format. */
-    CODABAR,
+    CODABAR = 1,
 
     /** Code 39 1D format. */
-    Code39,
+    Code39 = 2,
 
     /** Code 93 1D format. */
-    Code93,
+    Code93 = 3,
 
     /** Code 128 1D format. */
-    Code128,
+    Code128 = 4,
 
     /** Data Matrix 2D barcode format. */
-    DataMatrix,
+    DataMatrix = 5,
 
     /** EAN-8 1D format. */
-    Ean8,
+    Ean8 = 6,
 
     /** EAN-13 1D format. */
-    Ean13,
+    Ean13 = 7,
 
     /** ITF (Interleaved Two of Five) 1D format. */
-    ITF,
+    ITF = 8,
 
     /** MaxiCode 2D barcode format. */
-    MAXICODE,
+    MAXICODE = 9,
 
     /** PDF417 format. */
-    Pdf417,
+    Pdf417 = 10,
 
     /** QR Code 2D barcode format. */
-    QrCode,
+    QrCode = 11,
 
     /** RSS 14 */
-    Rss14,
+    Rss14 = 12,
 
     /** RSS EXPANDED */
-    RssExpanded,
+    RssExpanded = 13,
 
     /** UPC-A 1D format. */
-    UpcA,
+    UpcA = 14,
 
     /** UPC-E 1D format. */
-    UpcE,
+    UpcE = 15,
 
     /** UPC/EAN extension format. Not a stand-alone format. */
-    UpcEanExtension,
+    UpcEanExtension = 16,
 
-    ///
-    UnsupportedFormat,
+    /** Unsupported format. */
+    UnsupportedFormat = 17,
+}
+
+impl JsBarcodeFormat {
+    /// Stable numeric code of the format, as exposed by `DecodeResult.formatCode`.
+    pub fn code(self) -> u32 {
+        self as u32
+    }
+
+    /// Whether this is a linear (1D) format, read along a single line.
+    pub fn is_linear(self) -> bool {
+        matches!(
+            self,
+            JsBarcodeFormat::CODABAR
+                | JsBarcodeFormat::Code39
+                | JsBarcodeFormat::Code93
+                | JsBarcodeFormat::Code128
+                | JsBarcodeFormat::Ean8
+                | JsBarcodeFormat::Ean13
+                | JsBarcodeFormat::ITF
+                | JsBarcodeFormat::Rss14
+                | JsBarcodeFormat::RssExpanded
+                | JsBarcodeFormat::UpcA
+                | JsBarcodeFormat::UpcE
+                | JsBarcodeFormat::UpcEanExtension
+        )
+    }
 }
 
 impl From<JsBarcodeFormat> for BarcodeFormat {
@@ -0,0 +1,86 @@
+//! Locating the FNC function codes of Code 128 symbols, which rxing consumes while decoding their text.
+
+const CODE_FNC_3: u8 = 96;
+const CODE_FNC_2: u8 = 97;
+const CODE_SHIFT: u8 = 98;
+const CODE_CODE_C: u8 = 99;
+const CODE_CODE_B: u8 = 100;
+const CODE_CODE_A: u8 = 101;
+const CODE_FNC_1: u8 = 102;
+const CODE_START_A: u8 = 103;
+const CODE_START_B: u8 = 104;
+const CODE_START_C: u8 = 105;
+
+#[derive(Clone, Copy, PartialEq)]
+enum CodeSet {
+    A,
+    B,
+    C,
+}
+
+/// Finds the function codes among the codewords of a Code 128 symbol, as rxing reports them in its raw bytes (start,
+/// data, check and stop codewords), and returns them as (position, FNC number) pairs. The position is the index of
+/// the character of the decoded text the code precedes, counting the `]C1` prefix and GS separators that FNC1
+/// codes are rendered as when `convert_fnc1` (rxing's `ASSUME_GS1` hint) is set. Returns `None` for codewords that
+/// don't form a symbol.
+pub(crate) fn find_fnc_codes(codewords: &[u8], convert_fnc1: bool) -> Option<Vec<(usize, u8)>> {
+    let (&start, rest) = codewords.split_first()?;
+    // The last two codewords are the check and stop codes
+    let data = rest.get(..rest.len().checked_sub(2)?)?;
+    let mut code_set = match start {
+        CODE_START_A => CodeSet::A,
+        CODE_START_B => CodeSet::B,
+        CODE_START_C => CodeSet::C,
+        _ => return None,
+    };
+
+    let mut fnc_codes = Vec::new();
+    let mut position = 0;
+    let mut shifted_from = None;
+    for &code in data {
+        let fnc = match (code_set, code) {
+            (CodeSet::C, 0..=99) => {
+                position += 2;
+                None
+            }
+            (CodeSet::A | CodeSet::B, 0..=95) => {
+                position += 1;
+                None
+            }
+            (_, CODE_FNC_1) => Some(1),
+            (CodeSet::A | CodeSet::B, CODE_FNC_2) => Some(2),
+            (CodeSet::A | CodeSet::B, CODE_FNC_3) => Some(3),
+            (CodeSet::A, CODE_CODE_A) | (CodeSet::B, CODE_CODE_B) => Some(4),
+            (CodeSet::A | CodeSet::B, CODE_SHIFT) => {
+                shifted_from = Some(code_set);
+                code_set = if code_set == CodeSet::A { CodeSet::B } else { CodeSet::A };
+                continue;
+            }
+            (_, CODE_CODE_A) => {
+                code_set = CodeSet::A;
+                None
+            }
+            (_, CODE_CODE_B) => {
+                code_set = CodeSet::B;
+                None
+            }
+            (_, CODE_CODE_C) => {
+                code_set = CodeSet::C;
+                None
+            }
+            _ => return None,
+        };
+
+        if let Some(fnc) = fnc {
+            fnc_codes.push((position, fnc));
+            if fnc == 1 && convert_fnc1 {
+                position += if position == 0 { "]C1".len() } else { 1 };
+            }
+        }
+        if let Some(code_set_before_shift) = shifted_from.take() {
+            code_set = code_set_before_shift;
+        }
+    }
+
+    Some(fnc_codes)
+}
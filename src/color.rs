@@ -0,0 +1,43 @@
+use image::Rgb;
+
+/// Minimum contrast ratio between the foreground and the background for a code to stay scannable.
+pub(crate) const MIN_CONTRAST_RATIO: f64 = 3.0;
+
+pub(crate) const WHITE: Rgb<u8> = Rgb([u8::MAX, u8::MAX, u8::MAX]);
+
+/// Parses a `#rgb` or `#rrggbb` hex color, the leading `#` being optional.
+pub(crate) fn parse_color(color: &str) -> Option<Rgb<u8>> {
+    let hex = color.trim().trim_start_matches('#');
+    let channel = |index: usize, len: usize| u8::from_str_radix(hex.get(index * len..(index + 1) * len)?, 16).ok();
+
+    match hex.len() {
+        3 => Some(Rgb([channel(0, 1)? * 17, channel(1, 1)? * 17, channel(2, 1)? * 17])),
+        6 => Some(Rgb([channel(0, 2)?, channel(1, 2)?, channel(2, 2)?])),
+        _ => None,
+    }
+}
+
+/// Relative luminance of a color, as defined by WCAG 2.
+pub(crate) fn relative_luminance(color: Rgb<u8>) -> f64 {
+    let linear = |channel: u8| {
+        let channel = channel as f64 / 255.0;
+        if channel <= 0.03928 {
+            channel / 12.92
+        } else {
+            ((channel + 0.055) / 1.055).powf(2.4)
+        }
+    };
+
+    0.2126 * linear(color[0]) + 0.7152 * linear(color[1]) + 0.0722 * linear(color[2])
+}
+
+/// WCAG 2 contrast ratio between two colors, from 1 (identical) to 21 (black on white).
+pub(crate) fn contrast_ratio(a: Rgb<u8>, b: Rgb<u8>) -> f64 {
+    let (a, b) = (relative_luminance(a), relative_luminance(b));
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+/// Whether `foreground` modules drawn over `background` are dark enough to scan.
+pub(crate) fn is_scannable(foreground: Rgb<u8>, background: Rgb<u8>) -> bool {
+    relative_luminance(foreground) < relative_luminance(background) && contrast_ratio(foreground, background) >= MIN_CONTRAST_RATIO
+}
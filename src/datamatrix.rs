@@ -0,0 +1,142 @@
+//! Data Matrix encoding of GS1 data with FNC1 separators.
+//!
+//! rxing only encodes FNC1 separators in its minimal encoder, which mistakes them for ECIs and overflows, so GS1
+//! data is encoded here with ASCII encodation, reusing rxing's symbol tables, error correction and module placement.
+
+use rxing::common::BitMatrix;
+use rxing::datamatrix::encoder::{error_correction, DefaultPlacement, SymbolInfo, SymbolInfoLookup, SymbolShapeHint};
+
+const FNC1: u8 = 232;
+const PAD: u8 = 129;
+const UPPER_SHIFT: u8 = 235;
+const DIGIT_PAIR_OFFSET: u8 = 130;
+
+/// The GS character, which stands for FNC1 in GS1 data.
+pub(crate) const GROUP_SEPARATOR: char = '\x1D';
+
+/// Encodes GS1 `data`, where every GS character stands for an FNC1 separator, as the smallest square or rectangular
+/// symbol that fits, scaled like rxing's writer to fit `width` x `height`. A leading FNC1 marks the symbol as GS1.
+pub(crate) fn encode_gs1(data: &str, width: u32, height: u32) -> Option<BitMatrix> {
+    let mut codewords = vec![FNC1];
+    let chars: Vec<char> = data.chars().collect();
+    let mut index = 0;
+    while index < chars.len() {
+        let c = chars[index];
+        match chars.get(index + 1) {
+            Some(next) if c.is_ascii_digit() && next.is_ascii_digit() => {
+                codewords.push(DIGIT_PAIR_OFFSET + (c as u8 - b'0') * 10 + (*next as u8 - b'0'));
+                index += 1;
+            }
+            _ if c == GROUP_SEPARATOR => codewords.push(FNC1),
+            _ if c.is_ascii() => codewords.push(c as u8 + 1),
+            _ if (c as u32) <= 0xFF => codewords.extend([UPPER_SHIFT, (c as u32 - 127) as u8]),
+            _ => return None,
+        }
+        index += 1;
+    }
+
+    let lookup = SymbolInfoLookup::new();
+    let symbol_info = lookup.lookup_with_codewords_shape_size_fail(codewords.len() as u32, SymbolShapeHint::FORCE_NONE, &None, &None, true).ok()??;
+
+    let capacity = symbol_info.getDataCapacity() as usize;
+    if codewords.len() < capacity {
+        codewords.push(PAD);
+    }
+    while codewords.len() < capacity {
+        codewords.push(randomize_253_state(codewords.len() as u32 + 1));
+    }
+
+    let codewords: String = codewords.into_iter().map(char::from).collect();
+    let codewords = error_correction::encodeECC200(&codewords, symbol_info).ok()?;
+
+    let (data_width, data_height) = (symbol_info.getSymbolDataWidth().ok()?, symbol_info.getSymbolDataHeight().ok()?);
+    let mut placement = DefaultPlacement::new(codewords, data_width as usize, data_height as usize);
+    placement.place().ok()?;
+
+    scale(&place_finder_patterns(&placement, symbol_info)?, width, height)
+}
+
+/// Pseudo random padding codeword for the (1-based) position, as defined by ISO/IEC 16022.
+fn randomize_253_state(position: u32) -> u8 {
+    let pseudo_random = ((149 * position) % 253) + 1;
+    let value = PAD as u32 + pseudo_random;
+    if value <= 254 {
+        value as u8
+    } else {
+        (value - 254) as u8
+    }
+}
+
+/// Lays out the data regions with the solid L shaped finder pattern and the alternating timing pattern around each
+/// of them, as one module per bit.
+fn place_finder_patterns(placement: &DefaultPlacement, symbol_info: &SymbolInfo) -> Option<BitMatrix> {
+    let (data_width, data_height) = (symbol_info.getSymbolDataWidth().ok()?, symbol_info.getSymbolDataHeight().ok()?);
+    let (symbol_width, symbol_height) = (symbol_info.getSymbolWidth().ok()?, symbol_info.getSymbolHeight().ok()?);
+    // Every data region adds two modules of patterns in each direction
+    let region_width = data_width / ((symbol_width - data_width) / 2);
+    let region_height = data_height / ((symbol_height - data_height) / 2);
+
+    let mut matrix = BitMatrix::new(symbol_width, symbol_height).ok()?;
+    let mut set = |x: u32, y: u32, value: bool| {
+        if value {
+            matrix.set(x, y);
+        }
+    };
+
+    let mut matrix_y = 0;
+    for y in 0..data_height {
+        if y % region_height == 0 {
+            for x in 0..symbol_width {
+                set(x, matrix_y, x % 2 == 0);
+            }
+            matrix_y += 1;
+        }
+
+        let mut matrix_x = 0;
+        for x in 0..data_width {
+            if x % region_width == 0 {
+                set(matrix_x, matrix_y, true);
+                matrix_x += 1;
+            }
+            set(matrix_x, matrix_y, placement.getBit(x as usize, y as usize));
+            matrix_x += 1;
+            if x % region_width == region_width - 1 {
+                set(matrix_x, matrix_y, y % 2 == 0);
+                matrix_x += 1;
+            }
+        }
+        matrix_y += 1;
+
+        if y % region_height == region_height - 1 {
+            for x in 0..symbol_width {
+                set(x, matrix_y, true);
+            }
+            matrix_y += 1;
+        }
+    }
+
+    Some(matrix)
+}
+
+/// Scales the symbol by the largest whole factor that fits and centers it, or leaves it unscaled if it doesn't fit.
+fn scale(symbol: &BitMatrix, width: u32, height: u32) -> Option<BitMatrix> {
+    let (symbol_width, symbol_height) = (symbol.getWidth(), symbol.getHeight());
+    if width < symbol_width || height < symbol_height {
+        return Some(symbol.clone());
+    }
+
+    let multiple = (width / symbol_width).min(height / symbol_height);
+    let left = (width - symbol_width * multiple) / 2;
+    let top = (height - symbol_height * multiple) / 2;
+
+    let mut output = BitMatrix::new(width, height).ok()?;
+    for y in 0..symbol_height {
+        for x in 0..symbol_width {
+            if symbol.get(x, y) {
+                output.setRegion(left + x * multiple, top + y * multiple, multiple, multiple).ok()?;
+            }
+        }
+    }
+
+    Some(output)
+}
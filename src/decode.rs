@@ -1,19 +1,36 @@
 #![deny(clippy::all)]
 
+use std::borrow::Cow;
+use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use std::str;
+use std::time::Instant;
 
 use base64::Engine;
 use base64::engine::general_purpose;
 use data_url::DataUrl;
-use napi::bindgen_prelude::Either;
-use rxing::{BarcodeFormat, DecodeHintType, DecodeHintValue, DecodingHintDictionary, RXingResult};
+use napi::bindgen_prelude::{Either, Either3};
+use napi::{Env, Error, JsBuffer, JsBufferValue, JsFunction, Status};
+use rayon::prelude::*;
+use rxing::common::{BitArray, BitMatrix, HybridBinarizer};
+use rxing::multi::{GenericMultipleBarcodeReader, MultipleBarcodeReader};
+use rxing::pdf417::PDF417RXingResultMetadata;
+use rxing::qrcode::decoder::{ErrorCorrectionLevel, Version};
+use rxing::qrcode::detector::FinderPatternFinder;
+use rxing::{BarcodeFormat, Binarizer, BinaryBitmap, DecodeHintType, DecodeHintValue, DecodingHintDictionary, Exceptions, Luma8LuminanceSource, LuminanceSource, MultiFormatReader, MultiUseMultiFormatReader, Point, RXingResult, RXingResultMetadataType, RXingResultMetadataValue, Reader, ResultPoint};
 
 use crate::barcode_format::JsBarcodeFormat;
+use crate::Rect;
+
+/// A grayscale image as `(pixels, width, height)`.
+type LumaImage = (Vec<u8>, u32, u32);
+
+/// Turns a grayscale image into its black matrix, or `None` to use the default binarizer.
+type BinarizeFn<'a> = &'a dyn Fn(&LumaImage) -> Option<BitMatrix>;
 
 #[napi(object)]
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct DecodeOptions {
     pub try_harder: Option<bool>,
     pub decode_multi: Option<bool>,
@@ -27,34 +44,426 @@ pub struct DecodeOptions {
     pub allowed_ean_extensions: Option<Vec<u32>>,
     pub also_inverted: Option<bool>,
     pub other: Option<String>,
+    /** Estimate the size of a module, in pixels, and report it as `moduleSizePx` */
+    pub estimate_module_size: Option<bool>,
+    /** Estimate the scan resolution from the module size, reporting it as `estimatedDpi` */
+    pub estimate_dpi: Option<EstimateDpiOptions>,
+    /**
+     * Split the image into this many horizontal stripes and search them in parallel, returning the first hit.
+     * Only applies to single (non `decodeMulti`) decodes of raster images. Stripes span the whole width of the
+     * image, so only codes running vertically can be split across them, and those are not reassembled: rxing only
+     * reports complete reads, with both guard patterns in view. Stripes are at least 40 rows tall, so short images are
+     * split into fewer
+     */
+    pub parallel_stripes: Option<u32>,
+    /**
+     * Number of rows shared by adjacent stripes when `parallelStripes` is set, defaults to one stripe height. Must be
+     * at least the height of the barcode, or it may be cut in half and missed, so pass it for codes taller than a stripe
+     */
+    pub stripe_overlap: Option<u32>,
+    /** Compute a 0-1 score of how likely each result is to be a genuine read, reported as `trust` */
+    pub trust_score: Option<bool>,
+    /**
+     * If decoding with `barcodeFormat` fails, retry once with every format and flag results with `formatRelaxed`.
+     * Failed decodes take about twice as long
+     */
+    pub relax_formats_on_failure: Option<bool>,
+    /** Only search this region of the image, in pixels. Clamped to the image bounds, and must be at least 40 pixels wide and high */
+    pub crop: Option<Rect>,
+    /**
+     * Only search this region of the image, as fractions (0 to 1) of its width and height. Clamped to the image
+     * bounds, an `InvalidArg` error is thrown if any fraction is out of range or if `crop` is also set
+     */
+    pub crop_normalized: Option<NormalizedRect>,
+    /**
+     * Rotate the image clockwise by this many degrees, after cropping and before detection, to straighten a code
+     * skewed by a known angle. The corners exposed by the rotation are filled with the average brightness of the
+     * image, and the returned points are mapped back to the original image. Must be finite
+     */
+    pub rotate_degrees: Option<f64>,
+    /** Return what the detectors found, instead of `null`, when no barcode could be decoded */
+    pub detection_info: Option<bool>,
+    /** Only return these keys in `DecodeResult.metadata`, all of them are returned when unset */
+    pub metadata_keys: Option<Vec<String>>,
+    /**
+     * Remove a leading byte order mark from `text`, as some encoders prepend one. `rawBytes` are left intact.
+     * Off by default, so that `text` is exactly what was encoded
+     */
+    pub strip_bom: Option<bool>,
+    /**
+     * Form of `resultPoints` and `outline`: `"pixel"` (the default), `"normalized"` for fractions (0 to 1) of the
+     * image width and height, or `"both"` to add the normalized coordinates as `nx` and `ny`. Points of SVG
+     * inputs, whose size isn't known, are always in pixels. Other values throw an `InvalidArg` error
+     */
+    pub point_coordinates: Option<String>,
+    /**
+     * Parse the data of GS1 codes (GS1-128, GS1 DataBar, GS1 Data Matrix and GS1 QR Code) into `gs1`, by
+     * application identifier (AI). Implies `assumeGs1`, so GS1-128 `text` keeps its separators
+     */
+    pub parse_gs1: Option<bool>,
+    /**
+     * With `parseGs1`, convert the values of well known AIs: dates (such as 11, production date, or 17, expiration
+     * date) to ISO 8601 strings, `YYYY-MM` when the day is left out, and counts (30 and 37) and measures (310n to
+     * 369n, 390n and 392n) to numbers. Other values, and values that aren't valid for their AI, stay strings
+     */
+    pub normalize_gs1_values: Option<bool>,
+    /**
+     * Also return the structured metadata that `metadata` can't hold as strings, as objects such as
+     * `pdf417Metadata`
+     */
+    pub metadata_raw: Option<bool>,
+    /**
+     * Only decode the regions of the image with sharp edges, found by a quick pre-pass, which is much faster on
+     * large images where codes take up little space. Codes with little contrast, blurred ones and ones with very
+     * large modules may not stand out and then go unnoticed, so it trades some accuracy for speed. Only applies to
+     * raster images decoded with the default binarizer
+     */
+    pub candidate_regions: Option<bool>,
+    /**
+     * Record the stages of the decode in order, with their duration and whether they found a barcode, as `attempts`
+     * on the results, or on the `DetectionInfo` of failed decodes when `detectionInfo` is set. The stages are
+     * `load` (reading the image), `crop`, `rotate`, `binarize` (the custom binarizer), `decode`, `decode inverted`
+     * (the `alsoInverted` retry), `relaxed formats` (`relaxFormatsOnFailure`, also followed by an inverted retry)
+     * and `detection info`. The retries rxing makes on its own, such as rotating for 1D codes with `tryHarder`, are
+     * part of the stage they happen in
+     */
+    pub attempt_timeline: Option<bool>,
+    /**
+     * Report the FNC1 to FNC4 function codes of Code 128 symbols as `fnc`. rxing leaves them out of `text`, apart
+     * from FNC1 with `assumeGs1`, even though they change how it should be read, such as FNC1 marking GS1 data
+     */
+    pub return_fnc_codes: Option<bool>,
+    /**
+     * With `pureBarcode`, skip detection and read the modules of a 2D code (QR Code, Data Matrix, Aztec, ...) of
+     * known size straight from the image, for setups that frame a single clean code. The dark pixels of the image,
+     * after `crop` and `rotateDegrees`, are taken to span exactly the `rows` by `cols` modules of the symbol, quiet
+     * zone excluded: the code must be upright, with square corners and evenly sized modules, and nothing else dark
+     * may be in view. Each module is read from the pixel at its center. An `InvalidArg` error is thrown if
+     * `pureBarcode` isn't set, and decoding fails for SVG files
+     */
+    pub pure_grid: Option<PureGrid>,
+    /**
+     * With `decodeMulti`, also decode every candidate region of the image (see `candidateRegions`) both as is and
+     * inverted, for scenes mixing dark on light and light on dark codes, such as a screen photographed next to
+     * paper, where `alsoInverted` only retries inverted where rxing found nothing and can miss some. The results are
+     * merged, dropping the codes found more than once. On top of the usual decode of the whole image, every region is
+     * decoded twice, in parallel, so expect decodes to take two to three times as long
+     */
+    pub auto_invert_per_region: Option<bool>,
+}
+
+/**
+ * A rectangular region of an image, as fractions of its width and height
+ */
+#[napi(object)]
+#[derive(Clone)]
+pub struct NormalizedRect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+/**
+ * The number of modules of a 2D code, for `pureGrid`
+ */
+#[napi(object)]
+#[derive(Clone)]
+pub struct PureGrid {
+    pub rows: u32,
+    pub cols: u32,
+}
+
+#[napi(object)]
+#[derive(Clone)]
+pub struct EstimateDpiOptions {
+    /** The physical width of a single module (the X-dimension), in millimeters */
+    pub x_dimension_mm: f64,
 }
 
 #[napi(object)]
+#[derive(Clone)]
 pub struct DecodeResult {
     pub text: String,
     pub raw_bytes: Vec<u8>,
     pub num_bits: u32,
     pub format: JsBarcodeFormat,
+    /** Stable numeric id of `format`, suitable for storage */
+    pub format_code: u32,
+    /** Human readable name of `format`, as reported by rxing (e.g. `"qrcode"`) */
+    pub format_name: String,
+    /** Estimated width of a module in pixels, if `estimateModuleSize` or `estimateDpi` is set */
+    pub module_size_px: Option<f64>,
+    /** Estimated resolution of the source image in dots per inch, if `estimateDpi` is set */
+    pub estimated_dpi: Option<f64>,
+    /** How the input was interpreted: `"file"`, `"data_url"`, `"base64"` or `"unknown"` */
+    pub input_kind: String,
+    /**
+     * Confidence that the result is not a false positive, from 0 to 1, if `trustScore` is set.
+     *
+     * This is a weighted average of the signals that apply to the format:
+     * - 0.4: check digit, 1 for formats with a mandatory checksum or error correction, 0.5 otherwise
+     * - 0.2: module size, reaching 1 at 2px or more
+     * - 0.2: quiet zone, the fraction of light pixels in the 5 modules on either side of a 1D code
+     * - 0.2: decode margin, the error correction level of QR Code and PDF417 symbols
+     */
+    pub trust: Option<f64>,
+    /** Set to `true` when the result was only found after `relaxFormatsOnFailure` dropped the format filter */
+    pub format_relaxed: Option<bool>,
+    /**
+     * Metadata reported by the reader, keyed by rxing's metadata type (e.g. `ORIENTATION`, `SYMBOLOGY_IDENTIFIER`).
+     * Numbers and booleans are formatted as strings and `BYTE_SEGMENTS` as comma separated base64 segments.
+     * Restricted to `metadataKeys` when set
+     */
+    pub metadata: HashMap<String, String>,
+    /**
+     * Outline of the code as an ordered polygon, clockwise in image space and starting from the corner closest to
+     * the top left of the image. 2D codes are outlined from their finder or corner points and 1D codes with the four
+     * corners of their bars. Empty when the reader reported no points
+     */
+    pub outline: Vec<ImagePoint>,
+    /** The points reported by the reader, such as QR code finder pattern centers or the ends of a 1D scan line */
+    pub result_points: Vec<ImagePoint>,
+    /**
+     * The values of the GS1 element strings by AI, if `parseGs1` is set and the code holds GS1 data that could be
+     * parsed. Values are strings unless `normalizeGs1Values` converted them
+     */
+    pub gs1: Option<HashMap<String, Either<String, f64>>>,
+    /** The Macro PDF417 control block of PDF417 symbols split over several codes, if `metadataRaw` is set */
+    pub pdf417_metadata: Option<Pdf417Metadata>,
+    /** The stages of the decode, if `attemptTimeline` is set */
+    pub attempts: Option<Vec<Attempt>>,
+    /** The function codes of Code 128 symbols in the order they appear, if `returnFncCodes` is set */
+    pub fnc: Option<Vec<FncCode>>,
+}
+
+/**
+ * A function code of a Code 128 symbol, as reported by `returnFncCodes`
+ */
+#[napi(object)]
+#[derive(Clone)]
+pub struct FncCode {
+    /**
+     * Index in `text` of the character the code precedes. With `assumeGs1`, FNC1 codes are in `text`, as the `]C1`
+     * prefix or a GS character, and this is the index where they start
+     */
+    pub position: u32,
+    /** Which function code it is, from 1 for FNC1 to 4 for FNC4 */
+    #[napi(js_name = "type")]
+    pub fnc_type: u32,
+}
+
+/**
+ * A stage of a decode, as recorded by `attemptTimeline`
+ */
+#[napi(object)]
+#[derive(Clone)]
+pub struct Attempt {
+    pub stage: String,
+    /** Duration of the stage, in milliseconds */
+    pub ms: f64,
+    /** Whether the stage found a barcode, always `false` for the stages preparing the image */
+    pub found: bool,
+}
+
+/**
+ * The Macro PDF417 control block, describing how a file is split over several PDF417 symbols.
+ * Optional fields are only set when the symbol carries them
+ */
+#[napi(object)]
+#[derive(Clone)]
+pub struct Pdf417Metadata {
+    /** Index of this symbol among the segments of the file, from 0 */
+    pub segment_index: u32,
+    /** Identifier shared by all the segments of the file */
+    pub file_id: String,
+    /** Whether this symbol is the last segment of the file */
+    pub last_segment: bool,
+    pub segment_count: Option<i32>,
+    pub sender: Option<String>,
+    pub addressee: Option<String>,
+    pub file_name: Option<String>,
+    /** Size of the file in bytes */
+    pub file_size: Option<i64>,
+    /** Timestamp of the file, in seconds since the Unix epoch */
+    pub timestamp: Option<i64>,
+    /** CRC-16 checksum of the file */
+    pub checksum: Option<i32>,
+}
+
+impl From<&PDF417RXingResultMetadata> for Pdf417Metadata {
+    fn from(value: &PDF417RXingResultMetadata) -> Self {
+        let text = |text: &str| Some(text.to_string()).filter(|text| !text.is_empty());
+
+        Pdf417Metadata {
+            segment_index: value.getSegmentIndex() as u32,
+            file_id: value.getFileId().to_string(),
+            last_segment: value.isLastSegment(),
+            // rxing leaves unset numbers at -1
+            segment_count: Some(value.getSegmentCount() as i32).filter(|&count| count >= 0),
+            sender: text(value.getSender()),
+            addressee: text(value.getAddressee()),
+            file_name: text(value.getFileName()),
+            file_size: Some(value.getFileSize()).filter(|&size| size >= 0),
+            timestamp: Some(value.getTimestamp()).filter(|&timestamp| timestamp >= 0),
+            checksum: Some(value.getChecksum()).filter(|&checksum| checksum >= 0),
+        }
+    }
+}
+
+/**
+ * A point in an image, in pixels unless `pointCoordinates` is `"normalized"`
+ */
+#[napi(object)]
+#[derive(Clone)]
+pub struct ImagePoint {
+    pub x: f64,
+    pub y: f64,
+    /** `x` as a fraction of the image width, if `pointCoordinates` is `"both"` */
+    pub nx: Option<f64>,
+    /** `y` as a fraction of the image height, if `pointCoordinates` is `"both"` */
+    pub ny: Option<f64>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum PointCoordinates {
+    Pixel,
+    Normalized,
+    Both,
+}
+
+impl PointCoordinates {
+    fn parse(point_coordinates: Option<&str>) -> Option<Self> {
+        match point_coordinates.unwrap_or("pixel") {
+            "pixel" => Some(PointCoordinates::Pixel),
+            "normalized" => Some(PointCoordinates::Normalized),
+            "both" => Some(PointCoordinates::Both),
+            _ => None,
+        }
+    }
+
+    /// Converts a point, leaving it in pixels when the image size is unknown.
+    fn convert(self, point: Point, image_size: Option<(u32, u32)>) -> ImagePoint {
+        let (x, y) = (point.getX() as f64, point.getY() as f64);
+        let normalized = image_size.map(|(width, height)| (x / width as f64, y / height as f64));
+
+        match (self, normalized) {
+            (PointCoordinates::Normalized, Some((nx, ny))) => ImagePoint { x: nx, y: ny, nx: None, ny: None },
+            (PointCoordinates::Both, Some((nx, ny))) => ImagePoint { x, y, nx: Some(nx), ny: Some(ny) },
+            _ => ImagePoint { x, y, nx: None, ny: None },
+        }
+    }
 }
 
 impl From<RXingResult> for DecodeResult {
     fn from(value: RXingResult) -> Self {
+        let format: JsBarcodeFormat = (*value.getBarcodeFormat()).into();
+
         DecodeResult {
             text: value.getText().to_string(),
             raw_bytes: value.getRawBytes().to_vec(),
             num_bits: value.getNumBits() as u32,
-            format: (*value.getBarcodeFormat()).into(),
+            format,
+            format_code: format.code(),
+            format_name: value.getBarcodeFormat().to_string(),
+            module_size_px: None,
+            estimated_dpi: None,
+            input_kind: InputKind::Unknown.as_str().to_string(),
+            trust: None,
+            format_relaxed: None,
+            metadata: value.getRXingResultMetadata().iter()
+                .filter_map(|(key, value)| Some((format!("{key:?}"), metadata_value_to_string(value)?)))
+                .collect(),
+            outline: Vec::new(),
+            result_points: value.getPoints().iter().map(|&point| PointCoordinates::Pixel.convert(point, None)).collect(),
+            gs1: None,
+            pdf417_metadata: None,
+            attempts: None,
+            fnc: None,
         }
     }
 }
 
+/// Parses the GS1 element strings of results whose symbology identifier marks them as GS1.
+fn parse_gs1_elements(result: &RXingResult, normalize: bool) -> Option<HashMap<String, Either<String, f64>>> {
+    let Some(RXingResultMetadataValue::SymbologyIdentifier(symbology_identifier)) =
+        result.getRXingResultMetadata().get(&RXingResultMetadataType::SYMBOLOGY_IDENTIFIER) else {
+        return None;
+    };
+    if !crate::gs1::is_gs1(symbology_identifier) {
+        return None;
+    }
+
+    // Converted GS1-128 text starts with the symbology identifier, and text that wasn't converted has lost its
+    // separators, so can't be split
+    let text = result.getText();
+    let text = match text.strip_prefix(symbology_identifier.as_str()) {
+        Some(stripped) => stripped,
+        None if *result.getBarcodeFormat() == BarcodeFormat::CODE_128 => return None,
+        None => text,
+    };
+
+    // GS1 DataBar Omnidirectional only holds a GTIN, without its AI
+    let elements = if *result.getBarcodeFormat() == BarcodeFormat::RSS_14 {
+        vec![("01".to_string(), text.to_string())]
+    } else {
+        crate::gs1::parse_element_strings(text)?
+    };
+
+    Some(elements.into_iter().map(|(ai, value)| {
+        let value = if normalize { crate::gs1::normalize_value(&ai, &value) } else { Either::A(value) };
+        (ai, value)
+    }).collect())
+}
+
+fn metadata_value_to_string(value: &RXingResultMetadataValue) -> Option<String> {
+    let value = match value {
+        RXingResultMetadataValue::OTHER(value)
+        | RXingResultMetadataValue::ErrorCorrectionLevel(value)
+        | RXingResultMetadataValue::SuggestedPrice(value)
+        | RXingResultMetadataValue::PossibleCountry(value)
+        | RXingResultMetadataValue::UpcEanExtension(value)
+        | RXingResultMetadataValue::SymbologyIdentifier(value)
+        | RXingResultMetadataValue::ContentType(value) => value.clone(),
+        RXingResultMetadataValue::Orientation(value)
+        | RXingResultMetadataValue::IssueNumber(value)
+        | RXingResultMetadataValue::StructuredAppendSequence(value)
+        | RXingResultMetadataValue::StructuredAppendParity(value) => value.to_string(),
+        RXingResultMetadataValue::IsMirrored(value) => value.to_string(),
+        RXingResultMetadataValue::ByteSegments(segments) => {
+            segments.iter().map(|segment| general_purpose::STANDARD.encode(segment)).collect::<Vec<_>>().join(",")
+        }
+        // Structured data with no meaningful string form, returned as `pdf417Metadata` instead
+        RXingResultMetadataValue::Pdf417ExtraMetadata(_) => return None,
+    };
+
+    Some(value)
+}
+
+/**
+ * What the detectors found in an image where no barcode could be decoded
+ */
+#[napi(object)]
+pub struct DetectionInfo {
+    /** Number of QR code finder patterns (the squares in three of its corners) found */
+    pub finder_patterns_found: u32,
+    /** Whether three finder patterns were found in a plausible arrangement for a QR code */
+    pub qr_code_located: bool,
+    /** Whether the outline of a Data Matrix code was found */
+    pub data_matrix_located: bool,
+    /** Whether the bullseye and orientation marks of an Aztec code were found */
+    pub aztec_located: bool,
+    /** The stages of the decode, if `attemptTimeline` is set */
+    pub attempts: Option<Vec<Attempt>>,
+}
+
 /**
  * Decode a barcode from a file or base64 string
  *
  * @param {string} input Either a path to a file or a base64 string
  * @param {DecodeOptions} [options] Optional options to pass to the decoder
  *
- * @returns {DecodeResult|Array<DecodeResult>|null} The decode result or a list of decode results if `options.decodeMulti` is set to `true`, or `null` if the barcode could not be decoded or encountered an error
+ * @returns {DecodeResult|Array<DecodeResult>|DetectionInfo|null} The decode result or a list of decode results if `options.decodeMulti` is set to `true`, or `null` if the barcode could not be decoded or encountered an error. When `options.detectionInfo` is set, a failed decode returns what the detectors found instead of `null`
  *
  * @example
  * const { decode } = require('@rxing/rxing');
@@ -65,24 +474,755 @@ impl From<RXingResult> for DecodeResult {
  * console.log(result.text);
  */
 #[napi]
-pub fn decode(input: String, options: Option<DecodeOptions>) -> Option<Either<DecodeResult, Vec<DecodeResult>>> {
+pub fn decode(input: String, options: Option<DecodeOptions>) -> napi::Result<Option<Either3<DecodeResult, Vec<DecodeResult>, DetectionInfo>>> {
     let options = options.unwrap_or_default();
+    validate_options(&options)?;
+    Ok(decode_input(&input, options, None))
+}
+
+/**
+ * Decode a barcode from a file or base64 string, using a custom binarizer
+ *
+ * The binarizer is called once, with the grayscale pixels of the image (one byte per pixel, row by row), and must
+ * return a 1 bit per pixel bitmap: rows are padded to whole bytes, the most significant bit comes first, and a set
+ * bit is dark. If it throws or returns anything else, rxing's default binarizer is used instead. Derived images
+ * (rotated for `tryHarder`, or inverted for `alsoInverted`) always use the default binarizer, and `parallelStripes`,
+ * `candidateRegions` and `autoInvertPerRegion`, which search parts of the image on their own, can't be combined with
+ * it: they throw an `InvalidArg` error.
+ *
+ * @param {string} input Either a path to a file or a base64 string
+ * @param {(luma: Buffer, width: number, height: number) => Buffer} binarizer The binarizer callback
+ * @param {DecodeOptions} [options] Optional options to pass to the decoder
+ *
+ * @returns {DecodeResult|Array<DecodeResult>|DetectionInfo|null} The decode result or a list of decode results if `options.decodeMulti` is set to `true`, or `null` if the barcode could not be decoded or encountered an error. When `options.detectionInfo` is set, a failed decode returns what the detectors found instead of `null`
+ *
+ * @example
+ * const { decodeWithBinarizer } = require('@rxing/rxing');
+ * const result = decodeWithBinarizer('path/to/file.png', (luma, width, height) => myModel.binarize(luma, width, height));
+ * console.log(result.text);
+ */
+#[napi(ts_args_type = "input: string, binarizer: (luma: Buffer, width: number, height: number) => Buffer, options?: DecodeOptions | undefined | null")]
+pub fn decode_with_binarizer(env: Env, input: String, binarizer: JsFunction, options: Option<DecodeOptions>) -> napi::Result<Option<Either3<DecodeResult, Vec<DecodeResult>, DetectionInfo>>> {
+    let options = options.unwrap_or_default();
+    validate_options(&options)?;
+    // These search parts of the image on their own, which the single bitmap of the callback doesn't cover
+    let unsupported = [
+        ("parallelStripes", stripe_count(&options).is_some()),
+        ("candidateRegions", options.candidate_regions.unwrap_or(false)),
+        ("autoInvertPerRegion", inverts_per_region(&options)),
+    ];
+    if let Some((option, _)) = unsupported.iter().find(|(_, set)| *set) {
+        return Err(Error::new(Status::InvalidArg, format!("{option} can't be combined with a custom binarizer")));
+    }
+
+    // `call` clears the exception of a callback that throws, the default binarizer is used instead
+    let binarize = |luma: &LumaImage| unpack_bitmap(&call_binarizer(&env, &binarizer, luma).ok()?, luma.1, luma.2);
+
+    Ok(decode_input(&input, options, Some(&binarize)))
+}
+
+/**
+ * Decode a barcode from a file or base64 string, returning only its text
+ *
+ * This skips building the full result, for loops where nothing else is needed. Options that only add fields to the
+ * result (such as `trustScore` or `estimateModuleSize`) have no effect, and `decodeMulti` is ignored: only the text
+ * of the first barcode found is returned.
+ *
+ * @param {string} input Either a path to a file or a base64 string
+ * @param {DecodeOptions} [options] Optional options to pass to the decoder
+ *
+ * @returns {string|null} The decoded text, or `null` if the barcode could not be decoded or encountered an error
+ *
+ * @example
+ * const { decodeText } = require('@rxing/rxing');
+ * const text = decodeText('path/to/file.png');
+ * console.log(text);
+ */
+#[napi]
+pub fn decode_text(input: String, options: Option<DecodeOptions>) -> napi::Result<Option<String>> {
+    let options = DecodeOptions { decode_multi: None, ..options.unwrap_or_default() };
+    validate_options(&options)?;
+
+    let Some(results) = run_detection(&input, &options, None).and_then(|detection| detection.results.ok()) else {
+        return Ok(None);
+    };
+    let Some(text) = results.first().map(|result| result.getText()) else {
+        return Ok(None);
+    };
+    if options.strip_bom.unwrap_or(false) {
+        Ok(Some(strip_byte_order_mark(text).to_string()))
+    } else {
+        Ok(Some(text.to_string()))
+    }
+}
+
+/// Strips a leading byte order mark, either decoded as such or as the Latin-1 rendering of a UTF-8 one.
+fn strip_byte_order_mark(text: &str) -> &str {
+    text.strip_prefix('\u{FEFF}')
+        .or_else(|| text.strip_prefix("\u{EF}\u{BB}\u{BF}"))
+        .unwrap_or(text)
+}
+
+/// Rejects options that can't be acted on, before anything is decoded.
+fn validate_options(options: &DecodeOptions) -> napi::Result<()> {
+    if options.rotate_degrees.is_some_and(|degrees| !degrees.is_finite()) {
+        return Err(Error::new(Status::InvalidArg, "rotateDegrees must be a finite number".to_string()));
+    }
+    if PointCoordinates::parse(options.point_coordinates.as_deref()).is_none() {
+        return Err(Error::new(Status::InvalidArg, "pointCoordinates must be \"pixel\", \"normalized\" or \"both\"".to_string()));
+    }
+    if options.pure_grid.is_some() && !options.pure_barcode.unwrap_or(false) {
+        return Err(Error::new(Status::InvalidArg, "pureGrid needs pureBarcode".to_string()));
+    }
+    if let Some(crop) = &options.crop_normalized {
+        if options.crop.is_some() {
+            return Err(Error::new(Status::InvalidArg, "crop and cropNormalized can't be combined".to_string()));
+        }
+        if [crop.x, crop.y, crop.width, crop.height].iter().any(|fraction| !(0.0..=1.0).contains(fraction)) {
+            return Err(Error::new(Status::InvalidArg, "cropNormalized fractions must be between 0 and 1".to_string()));
+        }
+    }
+
+    Ok(())
+}
+
+fn decode_input(input: &str, options: DecodeOptions, binarize: Option<BinarizeFn>) -> Option<Either3<DecodeResult, Vec<DecodeResult>, DetectionInfo>> {
+    let decode_multi = options.decode_multi.unwrap_or(false);
+    let x_dimension_mm = options.estimate_dpi.as_ref().map(|estimate_dpi| estimate_dpi.x_dimension_mm);
+    let estimate_module_size = options.estimate_module_size.unwrap_or(false) || x_dimension_mm.is_some();
+    let trust_score = options.trust_score.unwrap_or(false);
+
+    let Detection { input_kind, input, detect_input, black_matrix, results, format_relaxed, mut timeline } = run_detection(input, &options, binarize)?;
+    let results = match results {
+        Ok(results) => results,
+        Err(_) if options.detection_info.unwrap_or(false) => {
+            let detect_input = detect_input.as_ref().unwrap_or(&input);
+            let detection_info = timeline.record("detection info", || collect_detection_info(detect_input, black_matrix.as_ref(), &options), |_| false);
+            return detection_info.map(|detection_info| Either3::C(DetectionInfo { attempts: timeline.0, ..detection_info }));
+        }
+        Err(_) => return None,
+    };
+
+    // The outline of 1D codes is measured on the bars themselves
+    let has_linear_result = results.iter().any(|result| JsBarcodeFormat::from(*result.getBarcodeFormat()).is_linear());
+    let needs_luma = estimate_module_size || trust_score || has_linear_result;
+    let loaded_luma = match input {
+        Either::A(input_file) if needs_luma => load_luma_image(input_file),
+        _ => None,
+    };
+    let luma = match &input {
+        Either::A(_) => loaded_luma.as_ref(),
+        Either::B(luma_tuple) => Some(luma_tuple).filter(|_| needs_luma),
+    };
+
+    let assume_code39_check_digit = options.assume_code39_check_digit.unwrap_or(false);
+    let strip_bom = options.strip_bom.unwrap_or(false);
+    let point_coordinates = PointCoordinates::parse(options.point_coordinates.as_deref())?;
+    let parse_gs1 = options.parse_gs1.unwrap_or(false);
+    let normalize_gs1_values = options.normalize_gs1_values.unwrap_or(false);
+    let metadata_raw = options.metadata_raw.unwrap_or(false);
+    let return_fnc_codes = options.return_fnc_codes.unwrap_or(false);
+    // rxing renders FNC1 codes whenever the hint is set, even to `false`, and `parseGs1` sets it
+    let assume_gs1 = options.assume_gs1.is_some() || parse_gs1;
+    let image_size = match (&input, luma) {
+        _ if point_coordinates == PointCoordinates::Pixel => None,
+        (_, Some(luma_tuple)) | (Either::B(luma_tuple), None) => Some((luma_tuple.1, luma_tuple.2)),
+        (Either::A(input_file), None) if !is_svg(input_file) => image::image_dimensions(input_file).ok(),
+        (Either::A(_), None) => None,
+    };
+
+    let results: Vec<DecodeResult> = results.into_iter().map(|result| {
+        let module_size_px = luma.and_then(|luma| estimate_module_size_px(&result, luma));
+        let trust = if trust_score {
+            Some(compute_trust(&result, module_size_px, luma, assume_code39_check_digit))
+        } else {
+            None
+        };
+        let outline = compute_outline(&result, luma);
+        let result_points = result.getPoints().to_vec();
+        let gs1 = if parse_gs1 { parse_gs1_elements(&result, normalize_gs1_values) } else { None };
+        let pdf417_metadata = match result.getRXingResultMetadata().get(&RXingResultMetadataType::PDF417_EXTRA_METADATA) {
+            // rxing reports an empty control block for symbols without one, whose file id is otherwise mandatory
+            Some(RXingResultMetadataValue::Pdf417ExtraMetadata(pdf417_metadata)) if metadata_raw && !pdf417_metadata.getFileId().is_empty() => {
+                Some(pdf417_metadata.as_ref().into())
+            }
+            _ => None,
+        };
+        let fnc = match result.getBarcodeFormat() {
+            BarcodeFormat::CODE_128 if return_fnc_codes => crate::code128::find_fnc_codes(result.getRawBytes(), assume_gs1),
+            _ => None,
+        };
+        let mut decode_result: DecodeResult = result.into();
+
+        decode_result.outline = outline.into_iter().map(|point| point_coordinates.convert(point, image_size)).collect();
+        decode_result.result_points = result_points.into_iter().map(|point| point_coordinates.convert(point, image_size)).collect();
+        decode_result.input_kind = input_kind.as_str().to_string();
+        decode_result.trust = trust;
+        decode_result.format_relaxed = Some(format_relaxed).filter(|&relaxed| relaxed);
+        decode_result.gs1 = gs1;
+        decode_result.pdf417_metadata = pdf417_metadata;
+        decode_result.attempts = timeline.0.clone();
+        decode_result.fnc = fnc.map(|fnc| fnc.into_iter().map(|(position, fnc_type)| FncCode { position: position as u32, fnc_type: fnc_type as u32 }).collect());
+        if strip_bom {
+            decode_result.text = strip_byte_order_mark(&decode_result.text).to_string();
+        }
+        if let Some(metadata_keys) = &options.metadata_keys {
+            decode_result.metadata.retain(|key, _| metadata_keys.contains(key));
+        }
+        if estimate_module_size {
+            decode_result.module_size_px = module_size_px;
+        }
+        if let (Some(module_size_px), Some(x_dimension_mm)) = (module_size_px, x_dimension_mm) {
+            decode_result.estimated_dpi = Some(module_size_px / (x_dimension_mm / MM_PER_INCH));
+        }
+
+        decode_result
+    }).collect();
+
+    if decode_multi {
+        Some(Either3::B(results))
+    } else {
+        results.into_iter().next().map(Either3::A)
+    }
+}
+
+#[napi(object)]
+pub struct SheetReport {
+    /** Expected payloads that were found, in the order of `expected` */
+    pub matched: Vec<String>,
+    /** Expected payloads that were not found, in the order of `expected` */
+    pub missing: Vec<String>,
+    /** Codes found on the sheet that weren't expected, or that were found more times than expected */
+    pub extra: Vec<DecodeResult>,
+}
+
+/**
+ * Decode every barcode on a sheet and compare them against the payloads expected to be printed on it
+ *
+ * Payloads expected several times must be found as many times to all be matched.
+ *
+ * @param {string} input Either a path to a file or a base64 string
+ * @param {Array<string>} expected The payloads expected on the sheet
+ * @param {DecodeOptions} [options] Optional options to pass to the decoder, `decodeMulti` is always enabled
+ *
+ * @returns {SheetReport} The matched and missing payloads, and the unexpected codes. Every payload is missing if the input could not be read
+ *
+ * @example
+ * const { verifySheet } = require('@rxing/rxing');
+ * const report = verifySheet('path/to/sheet.png', ['LABEL-1', 'LABEL-2', 'LABEL-3']);
+ * console.log(report.missing);
+ */
+#[napi]
+pub fn verify_sheet(input: String, expected: Vec<String>, options: Option<DecodeOptions>) -> napi::Result<SheetReport> {
+    let options = DecodeOptions {
+        decode_multi: Some(true),
+        detection_info: None,
+        ..options.unwrap_or_default()
+    };
+    validate_options(&options)?;
+    let found = match decode_input(&input, options, None) {
+        Some(Either3::B(results)) => results,
+        _ => Vec::new(),
+    };
+
+    let mut unmatched: HashMap<&str, usize> = HashMap::new();
+    for result in &found {
+        *unmatched.entry(result.text.as_str()).or_default() += 1;
+    }
+
+    let (mut matched, mut missing) = (Vec::new(), Vec::new());
+    for payload in expected {
+        match unmatched.get_mut(payload.as_str()) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                matched.push(payload);
+            }
+            _ => missing.push(payload),
+        }
+    }
+
+    // Whatever is left unmatched is extra, keeping the last occurrences of payloads that were also matched
+    let mut extra = Vec::new();
+    for result in found.iter().rev() {
+        if let Some(count) = unmatched.get_mut(result.text.as_str()).filter(|count| **count > 0) {
+            *count -= 1;
+            extra.push(result.clone());
+        }
+    }
+    extra.reverse();
+
+    Ok(SheetReport { matched, missing, extra })
+}
+
+#[napi(object)]
+#[derive(Default)]
+pub struct VideoScannerOptions {
+    /** Options used to decode every frame, `decodeMulti` and `trustScore` are always enabled */
+    pub decode: Option<DecodeOptions>,
+    /** Number of frames a payload is remembered for after it was last seen, defaults to 10 */
+    pub window: Option<u32>,
+    /**
+     * Number of consecutive frames a payload must be seen in before it is reported, defaults to 2. A frame missing
+     * it starts the count over, once reported it stays so until it leaves the window
+     */
+    pub min_frames: Option<u32>,
+}
+
+struct TrackedRead {
+    best: DecodeResult,
+    /// Consecutive frames the payload was seen in, up to the last one it was seen in
+    streak: u32,
+    /// Whether the streak reached `minFrames` since the payload entered the window
+    confirmed: bool,
+    last_seen: u64,
+}
+
+/**
+ * Aggregates detections across the frames of a live video
+ *
+ * Reads are deduplicated by format and text, keeping the one with the highest `trust` of each payload until it has
+ * not been seen for `window` frames.
+ *
+ * @example
+ * const { VideoScanner } = require('@rxing/rxing');
+ * const scanner = new VideoScanner({ window: 15 });
+ * for (const frame of frames) {
+ *   const stable = scanner.pushFrame(frame);
+ *   console.log(stable.map((result) => result.text));
+ * }
+ */
+#[napi]
+pub struct VideoScanner {
+    options: DecodeOptions,
+    window: u32,
+    min_frames: u32,
+    frame: u64,
+    tracked: HashMap<(u32, String), TrackedRead>,
+}
+
+#[napi]
+impl VideoScanner {
+    #[napi(constructor)]
+    pub fn new(options: Option<VideoScannerOptions>) -> napi::Result<Self> {
+        let options = options.unwrap_or_default();
+        let decode_options = DecodeOptions {
+            decode_multi: Some(true),
+            trust_score: Some(true),
+            ..options.decode.unwrap_or_default()
+        };
+        validate_options(&decode_options)?;
+
+        Ok(VideoScanner {
+            options: decode_options,
+            window: options.window.unwrap_or(10).max(1),
+            min_frames: options.min_frames.unwrap_or(2).max(1),
+            frame: 0,
+            tracked: HashMap::new(),
+        })
+    }
+
+    /**
+     * Decode a frame and return the stable set of reads, sorted by decreasing `trust`
+     *
+     * @param {string} input Either a path to a file or a base64 string
+     *
+     * @returns {Array<DecodeResult>} The best read of every payload seen in at least `minFrames` consecutive frames, and within the last `window` frames
+     */
+    #[napi]
+    pub fn push_frame(&mut self, input: String) -> Vec<DecodeResult> {
+        self.frame += 1;
+
+        if let Some(Either3::B(results)) = decode_input(&input, self.options.clone(), None) {
+            for result in results {
+                let key = (result.format_code, result.text.clone());
+                let frame = self.frame;
+
+                let tracked = self.tracked.entry(key).or_insert_with(|| TrackedRead { best: result.clone(), streak: 0, confirmed: false, last_seen: 0 });
+                // Count each payload once per frame, even if it appears several times in it
+                if tracked.last_seen != frame {
+                    tracked.streak = if tracked.last_seen + 1 == frame { tracked.streak + 1 } else { 1 };
+                    tracked.confirmed |= tracked.streak >= self.min_frames;
+                    tracked.last_seen = frame;
+                }
+                if result.trust > tracked.best.trust {
+                    tracked.best = result;
+                }
+            }
+        }
+
+        let (frame, window) = (self.frame, self.window as u64);
+        self.tracked.retain(|_, tracked| frame - tracked.last_seen < window);
+
+        let mut stable: Vec<DecodeResult> = self
+            .tracked
+            .values()
+            .filter(|tracked| tracked.confirmed)
+            .map(|tracked| tracked.best.clone())
+            .collect();
+        stable.sort_by(|a, b| b.trust.partial_cmp(&a.trust).unwrap_or(Ordering::Equal));
+
+        stable
+    }
+
+    /**
+     * Forget every tracked read
+     */
+    #[napi]
+    pub fn reset(&mut self) {
+        self.frame = 0;
+        self.tracked.clear();
+    }
+}
+
+const MIN_CROP_SIZE: u32 = 40;
+
+/// The outcome of running the detectors on an input, before the results are converted for JS.
+struct Detection<'a> {
+    input_kind: InputKind,
+    /// The whole input image
+    input: Either<&'a str, LumaImage>,
+    /// The image the detectors ran on, if the input was cropped or rotated
+    detect_input: Option<Either<&'a str, LumaImage>>,
+    /// The black matrix from the custom binarizer, if any
+    black_matrix: Option<BitMatrix>,
+    /// Results, with their points relative to the whole input image
+    results: Result<Vec<RXingResult>, Exceptions>,
+    format_relaxed: bool,
+    timeline: Timeline,
+}
+
+/// The stages of a decode and how long they took, only recorded when `attemptTimeline` is set.
+struct Timeline(Option<Vec<Attempt>>);
+
+impl Timeline {
+    fn new(enabled: bool) -> Self {
+        Timeline(enabled.then(Vec::new))
+    }
+
+    fn is_recording(&self) -> bool {
+        self.0.is_some()
+    }
+
+    /// Runs a stage, recording it along with whether `found` says its outcome is a barcode.
+    fn record<T>(&mut self, stage: &str, run: impl FnOnce() -> T, found: impl FnOnce(&T) -> bool) -> T {
+        if !self.is_recording() {
+            return run();
+        }
+
+        let start = Instant::now();
+        let outcome = run();
+        self.record_since(stage, start, found(&outcome));
+
+        outcome
+    }
+
+    /// Records a stage that ran from `start` until now.
+    fn record_since(&mut self, stage: &str, start: Instant, found: bool) {
+        if let Some(attempts) = &mut self.0 {
+            attempts.push(Attempt { stage: stage.to_string(), ms: start.elapsed().as_secs_f64() * 1000.0, found });
+        }
+    }
+}
+
+fn run_detection<'a>(input: &'a str, options: &DecodeOptions, binarize: Option<BinarizeFn>) -> Option<Detection<'a>> {
+    let wants_crop = options.crop.is_some() || options.crop_normalized.is_some();
+    let rotate_degrees = options.rotate_degrees.filter(|degrees| degrees % 360.0 != 0.0);
+
+    let mut timeline = Timeline::new(options.attempt_timeline.unwrap_or(false));
+
+    let start = Instant::now();
+    let (input_kind, input) = get_input(input);
+    if let Either::B(_) = input {
+        // Base64 input and data URLs are decoded right away
+        timeline.record_since("load", start, false);
+    }
+    // Stripes, candidate regions, crops, rotations and custom binarizers work on the raw pixels, so files have to be
+    // loaded up front. So does the timeline, to time the loading and the inverted retry on their own
+    let needs_pixels = stripe_count(options).is_some() || options.candidate_regions.unwrap_or(false) || inverts_per_region(options) || options.pure_grid.is_some() || binarize.is_some() || wants_crop || rotate_degrees.is_some() || timeline.is_recording();
+    let input = match input {
+        Either::A(input_file) if needs_pixels && !is_svg(input_file) => {
+            Either::B(timeline.record("load", || load_luma_image(input_file), |_| false)?)
+        }
+        input => input,
+    };
+
+    let crop = match &input {
+        Either::B(luma_tuple) if wants_crop => Some(resolve_crop(options, luma_tuple.1, luma_tuple.2)?),
+        // SVG files are rasterized by rxing and can't be cropped
+        Either::A(_) if wants_crop => return None,
+        _ => None,
+    };
+    let cropped_input = match (&input, &crop) {
+        (Either::B(luma_tuple), Some(crop)) => Some(timeline.record("crop", || crop_luma_image(luma_tuple, crop), |_| false)),
+        _ => None,
+    };
+
+    let (detect_input, rotation) = match (&input, rotate_degrees) {
+        (Either::B(luma_tuple), Some(degrees)) => {
+            let source = cropped_input.as_ref().unwrap_or(luma_tuple);
+            let (rotated, rotation) = timeline.record("rotate", || rotate_luma_image(source, degrees), |_| false);
+            (Some(Either::B(rotated)), Some(rotation))
+        }
+        // SVG files are rasterized by rxing and can't be rotated
+        (Either::A(_), Some(_)) => return None,
+        _ => (cropped_input.map(Either::B), None),
+    };
+    let detect_input_ref = detect_input.as_ref().unwrap_or(&input);
+
+    let black_matrix = match (detect_input_ref, binarize) {
+        (Either::B(luma_tuple), Some(binarize)) => timeline.record("binarize", || binarize(luma_tuple), |_| false),
+        _ => None,
+    };
+
+    let mut results = detect_stage(&mut timeline, "decode", detect_input_ref, options, black_matrix.as_ref());
+    let mut format_relaxed = false;
+    if results.is_err() && options.relax_formats_on_failure.unwrap_or(false) && options.barcode_format.is_some() {
+        let relaxed_options = DecodeOptions { barcode_format: None, ..options.clone() };
+        results = detect_stage(&mut timeline, "relaxed formats", detect_input_ref, &relaxed_options, black_matrix.as_ref());
+        format_relaxed = true;
+    }
+
+    // Report points relative to the whole, unrotated image
+    if let Ok(results) = &mut results {
+        for result in results.iter_mut() {
+            if let Some(rotation) = &rotation {
+                for point in result.getPointsMut() {
+                    *point = rotation.to_source(*point);
+                }
+            }
+            if let Some(crop) = &crop {
+                translate_points(result, crop.x as f32, crop.y as f32);
+            }
+        }
+    }
+
+    Some(Detection { input_kind, input, detect_input, black_matrix, results, format_relaxed, timeline })
+}
+
+/// Runs `detect` as a stage of the timeline. While the timeline is recorded, the `alsoInverted` retry that rxing
+/// would make on its own is made here instead, on an inverted copy of the image, to be timed as a stage of its own.
+fn detect_stage(timeline: &mut Timeline, stage: &str, input: &Either<&str, LumaImage>, options: &DecodeOptions, black_matrix: Option<&BitMatrix>) -> Result<Vec<RXingResult>, Exceptions> {
+    let found = |results: &Result<Vec<RXingResult>, Exceptions>| results.is_ok();
+    let luma_tuple = match input {
+        Either::B(luma_tuple) if timeline.is_recording() && options.also_inverted.unwrap_or(false) && black_matrix.is_none() => luma_tuple,
+        _ => return timeline.record(stage, || detect(input, options, black_matrix), found),
+    };
+
+    let options = DecodeOptions { also_inverted: Some(false), ..options.clone() };
+    let results = timeline.record(stage, || detect(input, &options, None), found);
+    if results.is_ok() {
+        return results;
+    }
+
+    timeline.record(&format!("{stage} inverted"), || detect(&Either::B(invert_luma_image(luma_tuple)), &options, None), found)
+}
+
+fn invert_luma_image((pixels, width, height): &LumaImage) -> LumaImage {
+    (pixels.iter().map(|&pixel| u8::MAX - pixel).collect(), *width, *height)
+}
+
+/// Converts the `crop` or `cropNormalized` option into a pixel rectangle within the image, clamped to its bounds.
+fn resolve_crop(options: &DecodeOptions, width: u32, height: u32) -> Option<Rect> {
+    let crop = match (&options.crop, &options.crop_normalized) {
+        (Some(crop), None) => crop.clone(),
+        (None, Some(crop)) => Rect {
+            x: (crop.x * width as f64).round() as u32,
+            y: (crop.y * height as f64).round() as u32,
+            width: (crop.width * width as f64).round() as u32,
+            height: (crop.height * height as f64).round() as u32,
+        },
+        _ => return None,
+    };
+
+    if crop.x >= width || crop.y >= height {
+        return None;
+    }
+    let crop = Rect {
+        width: crop.width.min(width - crop.x),
+        height: crop.height.min(height - crop.y),
+        ..crop
+    };
+
+    // rxing's global histogram fallback for smaller images panics on flat regions
+    if crop.width < MIN_CROP_SIZE || crop.height < MIN_CROP_SIZE {
+        None
+    } else {
+        Some(crop)
+    }
+}
+
+fn crop_luma_image(luma_tuple: &LumaImage, crop: &Rect) -> LumaImage {
+    let (pixels, width, _) = luma_tuple;
+    let mut cropped = Vec::with_capacity((crop.width * crop.height) as usize);
+
+    for y in crop.y..crop.y + crop.height {
+        let row = (y * width + crop.x) as usize;
+        cropped.extend_from_slice(&pixels[row..row + crop.width as usize]);
+    }
+
+    (cropped, crop.width, crop.height)
+}
+
+/// A clockwise rotation about the center of an image, onto a canvas large enough to hold all of it.
+struct Rotation {
+    cos: f32,
+    sin: f32,
+    source_center: Point,
+    rotated_center: Point,
+}
+
+impl Rotation {
+    fn new(degrees: f64, width: u32, height: u32) -> Self {
+        let (sin, cos) = (degrees as f32).to_radians().sin_cos();
+        let rotated_width = (width as f32 * cos.abs() + height as f32 * sin.abs()).ceil();
+        let rotated_height = (width as f32 * sin.abs() + height as f32 * cos.abs()).ceil();
+
+        Self {
+            cos,
+            sin,
+            source_center: Point::new(width as f32 / 2.0, height as f32 / 2.0),
+            rotated_center: Point::new(rotated_width / 2.0, rotated_height / 2.0),
+        }
+    }
+
+    /// Maps a point of the rotated image back to the source image.
+    fn to_source(&self, point: Point) -> Point {
+        let (dx, dy) = (point.getX() - self.rotated_center.getX(), point.getY() - self.rotated_center.getY());
+        Point::new(
+            self.source_center.getX() + self.cos * dx + self.sin * dy,
+            self.source_center.getY() - self.sin * dx + self.cos * dy,
+        )
+    }
+}
+
+/// Rotates the image clockwise with bilinear interpolation, filling the exposed corners with its mean brightness.
+fn rotate_luma_image(luma_tuple: &LumaImage, degrees: f64) -> (LumaImage, Rotation) {
+    let (pixels, width, height) = luma_tuple;
+    let rotation = Rotation::new(degrees, *width, *height);
+    let rotated_width = (rotation.rotated_center.getX() * 2.0) as u32;
+    let rotated_height = (rotation.rotated_center.getY() * 2.0) as u32;
+
+    let fill = (pixels.iter().map(|&pixel| pixel as u64).sum::<u64>() / pixels.len().max(1) as u64) as f32;
+    let sample = |x: i64, y: i64| {
+        if x < 0 || y < 0 || x >= *width as i64 || y >= *height as i64 {
+            fill
+        } else {
+            pixels[(y as u32 * width + x as u32) as usize] as f32
+        }
+    };
+
+    let mut rotated = Vec::with_capacity((rotated_width * rotated_height) as usize);
+    for y in 0..rotated_height {
+        for x in 0..rotated_width {
+            // Sample at pixel centers
+            let source = rotation.to_source(Point::new(x as f32 + 0.5, y as f32 + 0.5));
+            let (source_x, source_y) = (source.getX() - 0.5, source.getY() - 0.5);
+            let (x0, y0) = (source_x.floor(), source_y.floor());
+            let (fx, fy) = (source_x - x0, source_y - y0);
+            let (x0, y0) = (x0 as i64, y0 as i64);
+
+            let top = sample(x0, y0) * (1.0 - fx) + sample(x0 + 1, y0) * fx;
+            let bottom = sample(x0, y0 + 1) * (1.0 - fx) + sample(x0 + 1, y0 + 1) * fx;
+            rotated.push((top * (1.0 - fy) + bottom * fy).round() as u8);
+        }
+    }
+
+    ((rotated, rotated_width, rotated_height), rotation)
+}
+
+fn translate_points(result: &mut RXingResult, dx: f32, dy: f32) {
+    for point in result.getPointsMut() {
+        *point = Point::new(point.getX() + dx, point.getY() + dy);
+    }
+}
+
+/// Runs the location stage of the 2D detectors alone, to report how far they got on an image that failed to decode.
+fn collect_detection_info(input: &Either<&str, LumaImage>, black_matrix: Option<&BitMatrix>, options: &DecodeOptions) -> Option<DetectionInfo> {
+    let binarized;
+    let black_matrix = match (black_matrix, input) {
+        (Some(black_matrix), _) => black_matrix,
+        (None, Either::B(luma_tuple)) => {
+            binarized = binarize_default(luma_tuple.clone());
+            binarized.as_ref()?
+        }
+        (None, Either::A(input_file)) if !is_svg(input_file) => {
+            binarized = binarize_default(load_luma_image(input_file)?);
+            binarized.as_ref()?
+        }
+        (None, Either::A(_)) => return None,
+    };
+
+    let hints = create_hints(options);
+    let mut finder = FinderPatternFinder::new(black_matrix);
+    let _ = finder.find(&hints);
+    // Centers seen on a single row are mostly noise, real patterns get confirmed by several rows
+    let finder_patterns_found = finder.getPossibleCenters().iter().filter(|center| center.getCount() >= 2).count() as u32;
+
+    Some(DetectionInfo {
+        finder_patterns_found,
+        qr_code_located: rxing::qrcode::detector::Detector::new(black_matrix).detect_with_hints(&hints).is_ok(),
+        data_matrix_located: rxing::datamatrix::detector::Detector::new(black_matrix).and_then(|detector| detector.detect()).is_ok(),
+        aztec_located: rxing::aztec::detector::Detector::new(black_matrix).detect(false).is_ok(),
+        attempts: None,
+    })
+}
+
+fn binarize_default((pixels, width, height): LumaImage) -> Option<BitMatrix> {
+    let binarizer = HybridBinarizer::new(Luma8LuminanceSource::new(pixels, width, height));
+    binarizer.get_black_matrix().ok().cloned()
+}
+
+/// Runs detection on the input with the hints described by `options`.
+fn detect(input: &Either<&str, LumaImage>, options: &DecodeOptions, black_matrix: Option<&BitMatrix>) -> Result<Vec<RXingResult>, Exceptions> {
+    let decode_multi = options.decode_multi.unwrap_or(false);
+    let mut hints = create_hints(options);
+
+    if let Some(pure_grid) = &options.pure_grid {
+        return match input {
+            Either::B(luma_tuple) => detect_in_pure_grid(luma_tuple, pure_grid, black_matrix, &mut hints),
+            Either::A(_) => Err(Exceptions::illegal_argument_with("pureGrid needs a raster image")),
+        };
+    }
+
+    // `decodeWithBinarizer` rejects the options searching parts of the image, which this bitmap doesn't cover
+    if let (Either::B(luma_tuple), Some(black_matrix)) = (input, black_matrix) {
+        return detect_with_black_matrix(luma_tuple, black_matrix, decode_multi, &mut hints);
+    }
+
+    match (input, stripe_count(options)) {
+        (Either::B(luma_tuple), _) if inverts_per_region(options) => detect_with_region_inversion(luma_tuple, options),
+        (Either::B(luma_tuple), _) if options.candidate_regions.unwrap_or(false) => detect_in_candidate_regions(luma_tuple, options),
+        (Either::B(luma_tuple), Some(stripes)) => detect_in_stripes(luma_tuple, stripes, options.stripe_overlap, options),
+        (Either::B(luma_tuple), None) => detect_in_luma(luma_tuple.clone(), decode_multi, &mut hints),
+        (Either::A(input_file), _) => detect_in_file(input_file, decode_multi, &mut hints),
+    }
+}
+
+fn inverts_per_region(options: &DecodeOptions) -> bool {
+    options.auto_invert_per_region.unwrap_or(false) && options.decode_multi.unwrap_or(false)
+}
+
+fn stripe_count(options: &DecodeOptions) -> Option<u32> {
+    options.parallel_stripes.filter(|&stripes| stripes > 1 && !options.decode_multi.unwrap_or(false))
+}
+
+fn create_hints(options: &DecodeOptions) -> DecodingHintDictionary {
     let mut hints: DecodingHintDictionary = HashMap::new();
 
-    if let Some(other) = options.other {
-        hints.insert(DecodeHintType::OTHER, DecodeHintValue::Other(other));
+    if let Some(other) = &options.other {
+        hints.insert(DecodeHintType::OTHER, DecodeHintValue::Other(other.clone()));
     }
 
     if let Some(pure_barcode) = options.pure_barcode {
         hints.insert(DecodeHintType::PURE_BARCODE, DecodeHintValue::PureBarcode(pure_barcode));
     }
 
-    if let Some(character_set) = options.character_set {
-        hints.insert(DecodeHintType::CHARACTER_SET, DecodeHintValue::CharacterSet(character_set));
+    if let Some(character_set) = &options.character_set {
+        hints.insert(DecodeHintType::CHARACTER_SET, DecodeHintValue::CharacterSet(character_set.clone()));
     }
 
-    if let Some(allowed_lengths) = options.allowed_lengths {
-        hints.insert(DecodeHintType::ALLOWED_LENGTHS, DecodeHintValue::AllowedLengths(allowed_lengths));
+    if let Some(allowed_lengths) = &options.allowed_lengths {
+        hints.insert(DecodeHintType::ALLOWED_LENGTHS, DecodeHintValue::AllowedLengths(allowed_lengths.clone()));
     }
 
     if let Some(assume_code39_check_digit) = options.assume_code39_check_digit {
@@ -91,14 +1231,17 @@ pub fn decode(input: String, options: Option<DecodeOptions>) -> Option<Either<De
 
     if let Some(assume_gs1) = options.assume_gs1 {
         hints.insert(DecodeHintType::ASSUME_GS1, DecodeHintValue::AssumeGs1(assume_gs1));
+    } else if options.parse_gs1.unwrap_or(false) {
+        // rxing drops the FNC1 separators of GS1-128 data otherwise, merging variable length fields
+        hints.insert(DecodeHintType::ASSUME_GS1, DecodeHintValue::AssumeGs1(true));
     }
 
     if let Some(return_codabar_start_end) = options.return_codabar_start_end {
         hints.insert(DecodeHintType::RETURN_CODABAR_START_END, DecodeHintValue::ReturnCodabarStartEnd(return_codabar_start_end));
     }
 
-    if let Some(allowed_ean_extensions) = options.allowed_ean_extensions {
-        hints.insert(DecodeHintType::ALLOWED_EAN_EXTENSIONS, DecodeHintValue::AllowedEanExtensions(allowed_ean_extensions));
+    if let Some(allowed_ean_extensions) = &options.allowed_ean_extensions {
+        hints.insert(DecodeHintType::ALLOWED_EAN_EXTENSIONS, DecodeHintValue::AllowedEanExtensions(allowed_ean_extensions.clone()));
     }
 
     if let Some(also_inverted) = options.also_inverted {
@@ -109,44 +1252,82 @@ pub fn decode(input: String, options: Option<DecodeOptions>) -> Option<Either<De
     let try_harder = options.try_harder.unwrap_or(true);
     hints.insert(DecodeHintType::TRY_HARDER, DecodeHintValue::TryHarder(try_harder));
 
-    if let Some(barcode_format) = options.barcode_format {
-        let barcode_format: Vec<BarcodeFormat> = barcode_format.into_iter().map(|x| x.into()).collect();
+    if let Some(barcode_format) = &options.barcode_format {
+        let barcode_format: Vec<BarcodeFormat> = barcode_format.iter().map(|&x| x.into()).collect();
         hints.insert(DecodeHintType::POSSIBLE_FORMATS, DecodeHintValue::PossibleFormats(HashSet::from_iter(
             barcode_format.iter().copied(),
         )));
     }
 
-    let decode_multi = options.decode_multi.unwrap_or(false);
-    match get_input(&input) {
-        Either::A(input_file) => {
-            detect_in_file(input_file, decode_multi, &mut hints)
-        }
-        Either::B(luma_tuple) => {
-            detect_in_luma(luma_tuple, decode_multi, &mut hints)
+    hints
+}
+
+/**
+ * Report how `decode` would interpret an input, without decoding it
+ *
+ * @param {string} input Either a path to a file or a base64 string
+ *
+ * @returns {string} `"data_url"` or `"base64"` if the input parses as such, `"file"` if it names an existing file,
+ * and `"unknown"` otherwise (it will still be treated as a file path)
+ *
+ * @example
+ * const { classifyInput } = require('@rxing/rxing');
+ * console.log(classifyInput('path/to/file.png')); // "file"
+ */
+#[napi]
+pub fn classify_input(input: String) -> String {
+    read_input(&input).0.as_str().to_string()
+}
+
+#[derive(Clone, Copy)]
+enum InputKind {
+    File,
+    DataUrl,
+    Base64,
+    Unknown,
+}
+
+impl InputKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            InputKind::File => "file",
+            InputKind::DataUrl => "data_url",
+            InputKind::Base64 => "base64",
+            InputKind::Unknown => "unknown",
         }
     }
 }
 
-fn get_input(input: &str) -> Either<&str, (Vec<u8>, u32, u32)> {
+/// Classifies the input, returning the encoded image bytes if it is a data url or base64 string.
+fn read_input(input: &str) -> (InputKind, Option<Vec<u8>>) {
     match DataUrl::process(input) {
         Ok(data_url) => {
             if let Ok((body, _)) = data_url.decode_to_vec() {
-                Either::B(create_luma_image(&body))
-            } else {
-                Either::A(input)
+                return (InputKind::DataUrl, Some(body));
             }
         }
         Err(_) => { // invalid data url
             if let Ok(bytes) = general_purpose::STANDARD.decode(input.as_bytes()) {
-                Either::B(create_luma_image(&bytes))
-            } else {
-                Either::A(input)
+                return (InputKind::Base64, Some(bytes));
             }
         }
     }
+
+    if Path::new(input).is_file() {
+        (InputKind::File, None)
+    } else {
+        (InputKind::Unknown, None)
+    }
+}
+
+fn get_input(input: &str) -> (InputKind, Either<&str, LumaImage>) {
+    match read_input(input) {
+        (kind, Some(bytes)) => (kind, Either::B(create_luma_image(&bytes))),
+        (kind, None) => (kind, Either::A(input)),
+    }
 }
 
-fn create_luma_image(bytes: &[u8]) -> (Vec<u8>, u32, u32) {
+fn create_luma_image(bytes: &[u8]) -> LumaImage {
     let image = image::load_from_memory(bytes).unwrap();
     let image = image.to_luma8();
     let (width, height) = image.dimensions();
@@ -155,52 +1336,681 @@ fn create_luma_image(bytes: &[u8]) -> (Vec<u8>, u32, u32) {
     (image, width, height)
 }
 
-fn detect_in_file(input_file: &str, decode_multi: bool, hints: &mut DecodingHintDictionary) -> Option<Either<DecodeResult, Vec<DecodeResult>>> {
-    let path = Path::new(&input_file);
-    let extension = path.extension().unwrap_or_default();
+fn load_luma_image(input_file: &str) -> Option<LumaImage> {
+    let image = image::open(input_file).ok()?.to_luma8();
+    let (width, height) = image.dimensions();
+
+    Some((image.into_raw(), width, height))
+}
+
+fn is_svg(input_file: &str) -> bool {
+    Path::new(input_file).extension().unwrap_or_default() == "svg"
+}
 
+fn detect_in_file(input_file: &str, decode_multi: bool, hints: &mut DecodingHintDictionary) -> Result<Vec<RXingResult>, Exceptions> {
     if decode_multi {
-        let result = if extension == "svg" {
+        if is_svg(input_file) {
             rxing::helpers::detect_multiple_in_svg_with_hints(input_file, hints)
         } else {
             rxing::helpers::detect_multiple_in_file_with_hints(input_file, hints)
-        };
-        process_multi_result(result)
+        }
     } else {
-        let result = if extension == "svg" {
+        let result = if is_svg(input_file) {
             rxing::helpers::detect_in_svg_with_hints(input_file, None, hints)
         } else {
             rxing::helpers::detect_in_file_with_hints(input_file, None, hints)
         };
 
-        if let Ok(result) = result {
-            Some(Either::A(result.into()))
-        } else {
-            None
-        }
+        result.map(|result| vec![result])
     }
 }
 
-fn detect_in_luma(luma_tuple: (Vec<u8>, u32, u32), decode_multi: bool, hints: &mut DecodingHintDictionary) -> Option<Either<DecodeResult, Vec<DecodeResult>>> {
+fn detect_in_luma(luma_tuple: LumaImage, decode_multi: bool, hints: &mut DecodingHintDictionary) -> Result<Vec<RXingResult>, Exceptions> {
     if decode_multi {
-        let result = rxing::helpers::detect_multiple_in_luma_with_hints(luma_tuple.0, luma_tuple.1, luma_tuple.2, hints);
-        process_multi_result(result)
+        rxing::helpers::detect_multiple_in_luma_with_hints(luma_tuple.0, luma_tuple.1, luma_tuple.2, hints)
+    } else {
+        rxing::helpers::detect_in_luma_with_hints(luma_tuple.0, luma_tuple.1, luma_tuple.2, None, hints)
+            .map(|result| vec![result])
+    }
+}
+
+fn detect_with_black_matrix(luma_tuple: &LumaImage, black_matrix: &BitMatrix, decode_multi: bool, hints: &mut DecodingHintDictionary) -> Result<Vec<RXingResult>, Exceptions> {
+    hints
+        .entry(DecodeHintType::TRY_HARDER)
+        .or_insert(DecodeHintValue::TryHarder(true));
+
+    let source = Luma8LuminanceSource::new(luma_tuple.0.clone(), luma_tuple.1, luma_tuple.2);
+    let mut bitmap = BinaryBitmap::new(ExternalBinarizer::Precomputed { source, matrix: black_matrix.clone() });
+
+    if decode_multi {
+        GenericMultipleBarcodeReader::new(MultiUseMultiFormatReader::default()).decode_multiple_with_hints(&mut bitmap, hints)
+    } else {
+        MultiFormatReader::default().decode_with_hints(&mut bitmap, hints).map(|result| vec![result])
+    }
+}
+
+fn call_binarizer(env: &Env, binarizer: &JsFunction, luma: &LumaImage) -> napi::Result<JsBufferValue> {
+    let pixels = env.create_buffer_with_data(luma.0.clone())?.into_raw().into_unknown();
+    let width = env.create_uint32(luma.1)?.into_unknown();
+    let height = env.create_uint32(luma.2)?.into_unknown();
+
+    let bitmap: JsBuffer = binarizer.call(None, &[pixels, width, height])?.try_into()?;
+    bitmap.into_value()
+}
+
+/// Unpacks a 1 bit per pixel, byte aligned, most significant bit first bitmap, if it has the expected size.
+fn unpack_bitmap(bitmap: &[u8], width: u32, height: u32) -> Option<BitMatrix> {
+    let stride = width.div_ceil(8) as usize;
+    if bitmap.len() != stride * height as usize {
+        return None;
+    }
+
+    let mut matrix = BitMatrix::new(width, height).ok()?;
+    for y in 0..height {
+        for x in 0..width {
+            if bitmap[y as usize * stride + x as usize / 8] & (0x80 >> (x % 8)) != 0 {
+                matrix.set(x, y);
+            }
+        }
+    }
+
+    Some(matrix)
+}
+
+/// Serves a black matrix computed ahead of time by a JS callback. The callback can only be invoked up front, so any
+/// derived source (cropped, rotated, ...) is binarized by rxing's `HybridBinarizer` instead.
+enum ExternalBinarizer {
+    Precomputed { source: Luma8LuminanceSource, matrix: BitMatrix },
+    Fallback(HybridBinarizer<Luma8LuminanceSource>),
+}
+
+impl Binarizer for ExternalBinarizer {
+    type Source = Luma8LuminanceSource;
+
+    fn get_luminance_source(&self) -> &Self::Source {
+        match self {
+            ExternalBinarizer::Precomputed { source, .. } => source,
+            ExternalBinarizer::Fallback(binarizer) => binarizer.get_luminance_source(),
+        }
+    }
+
+    fn get_black_row(&self, y: usize) -> Result<Cow<'_, BitArray>, Exceptions> {
+        match self {
+            ExternalBinarizer::Precomputed { matrix, .. } => Ok(Cow::Owned(matrix.getRow(y as u32))),
+            ExternalBinarizer::Fallback(binarizer) => binarizer.get_black_row(y),
+        }
+    }
+
+    fn get_black_matrix(&self) -> Result<&BitMatrix, Exceptions> {
+        match self {
+            ExternalBinarizer::Precomputed { matrix, .. } => Ok(matrix),
+            ExternalBinarizer::Fallback(binarizer) => binarizer.get_black_matrix(),
+        }
+    }
+
+    fn create_binarizer(&self, source: Self::Source) -> Self {
+        ExternalBinarizer::Fallback(HybridBinarizer::new(source))
+    }
+
+    fn get_width(&self) -> usize {
+        self.get_luminance_source().get_width()
+    }
+
+    fn get_height(&self) -> usize {
+        self.get_luminance_source().get_height()
+    }
+}
+
+/// Size in pixels of the modules of the code redrawn by `detect_in_pure_grid`.
+const PURE_GRID_MODULE_SIZE: u32 = 4;
+/// Quiet zone around the code redrawn by `detect_in_pure_grid`, in modules.
+const PURE_GRID_QUIET_ZONE: u32 = 2;
+
+/// Reads the modules of a code spanning the dark pixels of the image from the centers of a `pure_grid` grid, and
+/// decodes them. The modules are redrawn as a clean code for rxing's readers, whose `PURE_BARCODE` path then reads
+/// them back without searching for the code.
+fn detect_in_pure_grid(luma_tuple: &LumaImage, pure_grid: &PureGrid, black_matrix: Option<&BitMatrix>, hints: &mut DecodingHintDictionary) -> Result<Vec<RXingResult>, Exceptions> {
+    let binarized;
+    let black_matrix = match black_matrix {
+        Some(black_matrix) => black_matrix,
+        None => {
+            binarized = binarize_default(luma_tuple.clone()).ok_or(Exceptions::NOT_FOUND)?;
+            &binarized
+        }
+    };
+
+    let PureGrid { rows, cols } = *pure_grid;
+    let [left, top, width, height] = black_matrix.getEnclosingRectangle().ok_or(Exceptions::NOT_FOUND)?;
+    if rows == 0 || cols == 0 || width < cols || height < rows {
+        return Err(Exceptions::illegal_argument_with("pureGrid doesn't fit the code"));
+    }
+    let (module_width, module_height) = (width as f32 / cols as f32, height as f32 / rows as f32);
+
+    let offset = PURE_GRID_QUIET_ZONE * PURE_GRID_MODULE_SIZE;
+    let (redrawn_width, redrawn_height) = (cols * PURE_GRID_MODULE_SIZE + 2 * offset, rows * PURE_GRID_MODULE_SIZE + 2 * offset);
+    let mut redrawn = BitMatrix::new(redrawn_width, redrawn_height)?;
+    for row in 0..rows {
+        for col in 0..cols {
+            let x = left + ((col as f32 + 0.5) * module_width) as u32;
+            let y = top + ((row as f32 + 0.5) * module_height) as u32;
+            if black_matrix.get(x, y) {
+                redrawn.setRegion(offset + col * PURE_GRID_MODULE_SIZE, offset + row * PURE_GRID_MODULE_SIZE, PURE_GRID_MODULE_SIZE, PURE_GRID_MODULE_SIZE)?;
+            }
+        }
+    }
+
+    let pixels = (0..redrawn_height)
+        .flat_map(|y| (0..redrawn_width).map(move |x| (x, y)))
+        .map(|(x, y)| if redrawn.get(x, y) { 0 } else { u8::MAX })
+        .collect();
+    let source = Luma8LuminanceSource::new(pixels, redrawn_width, redrawn_height);
+    let mut bitmap = BinaryBitmap::new(ExternalBinarizer::Precomputed { source, matrix: redrawn });
+    let mut result = MultiFormatReader::default().decode_with_hints(&mut bitmap, hints)?;
+
+    // Map the points of the redrawn code back onto the image
+    for point in result.getPointsMut() {
+        let x = left as f32 + (point.getX() - offset as f32) / PURE_GRID_MODULE_SIZE as f32 * module_width;
+        let y = top as f32 + (point.getY() - offset as f32) / PURE_GRID_MODULE_SIZE as f32 * module_height;
+        *point = Point::new(x, y);
+    }
+
+    Ok(vec![result])
+}
+
+/// Searches overlapping horizontal stripes of the image concurrently and returns the first barcode found.
+///
+/// rxing results and hints are not `Send`, so each worker builds its own hints and only reports which stripe
+/// produced a hit; that stripe is then decoded again on the calling thread to build the result.
+fn detect_in_stripes(luma: &LumaImage, stripes: u32, overlap: Option<u32>, options: &DecodeOptions) -> Result<Vec<RXingResult>, Exceptions> {
+    let (_, width, height) = luma;
+    // The detectors need at least as many rows as a crop, fewer stripes are searched rather than ones too small
+    let stripes = stripes.min(height / MIN_CROP_SIZE).max(1);
+    let stripe_height = height.div_ceil(stripes);
+    // Every code up to a stripe tall is then wholly inside one stripe, taller ones need `stripeOverlap`
+    let overlap = overlap.unwrap_or(stripe_height);
+
+    let stripe_bounds = |index: u32| {
+        let bottom = (index * stripe_height + stripe_height + overlap).min(*height);
+        // The last stripe starts higher when rounding left it too short
+        ((index * stripe_height).min(bottom.saturating_sub(MIN_CROP_SIZE)), bottom)
+    };
+    let decode_stripe = |index: u32| {
+        let (top, bottom) = stripe_bounds(index);
+        let pixels = luma.0[(top * width) as usize..(bottom * width) as usize].to_vec();
+        rxing::helpers::detect_in_luma_with_hints(pixels, *width, bottom - top, None, &mut create_hints(options))
+    };
+
+    let found = (0..stripes).into_par_iter().find_map_any(|index| decode_stripe(index).ok().map(|_| index));
+
+    let Some(index) = found else {
+        return Err(Exceptions::not_found_with("no barcode found in any stripe"));
+    };
+
+    let mut result = decode_stripe(index)?;
+    let (top, _) = stripe_bounds(index);
+    translate_points(&mut result, 0.0, top as f32);
+
+    Ok(vec![result])
+}
+
+/// Side of the square cells the candidate region pre-pass scores, in pixels.
+const CANDIDATE_CELL_SIZE: u32 = 32;
+/// Mean gradient, in levels per pixel, below which a cell is never a candidate, whatever the rest of the image.
+const MIN_CANDIDATE_GRADIENT: f64 = 8.0;
+
+/// Decodes the candidate regions of the image in parallel. Single decodes return the result of the most promising
+/// region that decodes, multiple decodes the results of every region.
+fn detect_in_candidate_regions(luma: &LumaImage, options: &DecodeOptions) -> Result<Vec<RXingResult>, Exceptions> {
+    let decode_multi = options.decode_multi.unwrap_or(false);
+    let decode_region = |region: &Rect| detect_in_luma(crop_luma_image(luma, region), decode_multi, &mut create_hints(options));
+
+    // Results can't be sent across threads, so as with stripes the regions that decode are decoded again here
+    let regions = propose_candidate_regions(luma);
+    let found: Vec<usize> = if decode_multi {
+        (0..regions.len()).into_par_iter().filter(|&index| decode_region(&regions[index]).is_ok()).collect()
     } else {
-        let result = rxing::helpers::detect_in_luma_with_hints(luma_tuple.0, luma_tuple.1, luma_tuple.2, None, hints);
+        (0..regions.len()).into_par_iter().find_first(|&index| decode_region(&regions[index]).is_ok()).into_iter().collect()
+    };
+
+    let mut results = Vec::new();
+    for index in found {
+        let region = &regions[index];
+        for mut result in decode_region(region)? {
+            translate_points(&mut result, region.x as f32, region.y as f32);
+            results.push(result);
+        }
+    }
+
+    if results.is_empty() {
+        Err(Exceptions::not_found_with("no barcode found in any candidate region"))
+    } else {
+        Ok(results)
+    }
+}
+
+/// Decodes every barcode of an image mixing both polarities: the whole image as usual, then every candidate region
+/// both as is and inverted, in parallel. Codes found by several of these decodes are only returned once.
+fn detect_with_region_inversion(luma: &LumaImage, options: &DecodeOptions) -> Result<Vec<RXingResult>, Exceptions> {
+    // The regions are inverted here, rxing would only invert the ones where it finds nothing
+    let region_options = DecodeOptions { also_inverted: None, ..options.clone() };
+    let regions = propose_candidate_regions(luma);
+    let decode_region = |&(index, inverted): &(usize, bool)| {
+        let region = crop_luma_image(luma, &regions[index]);
+        let region = if inverted { invert_luma_image(&region) } else { region };
+        detect_in_luma(region, true, &mut create_hints(&region_options))
+    };
+
+    // Results can't be sent across threads, so as with stripes the regions that decode are decoded again here
+    let attempts: Vec<(usize, bool)> = (0..regions.len()).flat_map(|index| [(index, false), (index, true)]).collect();
+    let found: Vec<(usize, bool)> = attempts.into_par_iter().filter(|attempt| decode_region(attempt).is_ok()).collect();
+
+    let mut results = detect_in_luma(luma.clone(), true, &mut create_hints(options)).unwrap_or_default();
+    for attempt in found {
+        let region = &regions[attempt.0];
+        for mut result in decode_region(&attempt)? {
+            translate_points(&mut result, region.x as f32, region.y as f32);
+            results.push(result);
+        }
+    }
+
+    let results = deduplicate_results(results);
+    if results.is_empty() {
+        Err(Exceptions::not_found_with("no barcode found in either polarity"))
+    } else {
+        Ok(results)
+    }
+}
+
+/// Drops the results repeating an earlier one, with the same format and text and centered within it.
+fn deduplicate_results(results: Vec<RXingResult>) -> Vec<RXingResult> {
+    // The bounds of the points, grown by half their largest side so that 1D codes, whose points are on a line,
+    // still cover an area
+    let bounds = |result: &RXingResult| {
+        let points = result.getPoints();
+        let (min_x, max_x) = points.iter().map(|point| point.getX()).fold((f32::MAX, f32::MIN), |(min, max), x| (min.min(x), max.max(x)));
+        let (min_y, max_y) = points.iter().map(|point| point.getY()).fold((f32::MAX, f32::MIN), |(min, max), y| (min.min(y), max.max(y)));
+        let padding = (max_x - min_x).max(max_y - min_y) / 2.0 + 1.0;
+        Some((min_x - padding, min_y - padding, max_x + padding, max_y + padding)).filter(|_| !points.is_empty())
+    };
+    let is_repeat = |kept: &RXingResult, result: &RXingResult| {
+        kept.getBarcodeFormat() == result.getBarcodeFormat() && kept.getText() == result.getText() && match (bounds(kept), bounds(result)) {
+            (Some((left, top, right, bottom)), Some((min_x, min_y, max_x, max_y))) => {
+                let (x, y) = ((min_x + max_x) / 2.0, (min_y + max_y) / 2.0);
+                (left..=right).contains(&x) && (top..=bottom).contains(&y)
+            }
+            _ => true,
+        }
+    };
+
+    let mut unique: Vec<RXingResult> = Vec::new();
+    for result in results {
+        if !unique.iter().any(|kept| is_repeat(kept, &result)) {
+            unique.push(result);
+        }
+    }
+
+    unique
+}
+
+/// Finds the regions of the image with sharp edges, as connected groups of cells whose mean gradient stands out
+/// from the rest of the image, padded with a cell on every side for the quiet zone. Regions are sorted by their
+/// total gradient, most promising first.
+fn propose_candidate_regions(luma: &LumaImage) -> Vec<Rect> {
+    let (pixels, width, height) = luma;
+    let (columns, rows) = (width.div_ceil(CANDIDATE_CELL_SIZE), height.div_ceil(CANDIDATE_CELL_SIZE));
 
-        if let Ok(result) = result {
-            Some(Either::A(result.into()))
+    // Every other pixel of every other row is enough to tell edges from flat areas
+    let gradients: Vec<f64> = (0..rows * columns).map(|cell| {
+        let (left, top) = ((cell % columns) * CANDIDATE_CELL_SIZE, (cell / columns) * CANDIDATE_CELL_SIZE);
+        let (right, bottom) = ((left + CANDIDATE_CELL_SIZE).min(width - 1), (top + CANDIDATE_CELL_SIZE).min(height - 1));
+        let (mut total, mut samples) = (0u32, 0u32);
+        for y in (top..bottom).step_by(2) {
+            for x in (left..right).step_by(2) {
+                let pixel = pixels[(y * width + x) as usize] as i32;
+                total += (pixels[(y * width + x + 1) as usize] as i32 - pixel).unsigned_abs()
+                    + (pixels[((y + 1) * width + x) as usize] as i32 - pixel).unsigned_abs();
+                samples += 1;
+            }
+        }
+        if samples == 0 { 0.0 } else { total as f64 / samples as f64 }
+    }).collect();
+
+    // An empty image has no cells, let alone a median one
+    if gradients.is_empty() {
+        return Vec::new();
+    }
+    let mut sorted = gradients.clone();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let threshold = MIN_CANDIDATE_GRADIENT.max(2.0 * sorted[sorted.len() / 2]);
+
+    // Group the candidate cells into 8-connected components
+    let mut visited = vec![false; gradients.len()];
+    let mut regions: Vec<(Rect, f64)> = Vec::new();
+    for start in 0..gradients.len() {
+        if visited[start] || gradients[start] < threshold {
+            continue;
+        }
+        visited[start] = true;
+        let mut stack = vec![start];
+        let (mut min_column, mut min_row, mut max_column, mut max_row) = (u32::MAX, u32::MAX, 0, 0);
+        let mut total = 0.0;
+        while let Some(cell) = stack.pop() {
+            let (column, row) = (cell as u32 % columns, cell as u32 / columns);
+            (min_column, min_row, max_column, max_row) = (min_column.min(column), min_row.min(row), max_column.max(column), max_row.max(row));
+            total += gradients[cell];
+            for (neighbor_column, neighbor_row) in (row.saturating_sub(1)..=(row + 1).min(rows - 1))
+                .flat_map(|y| (column.saturating_sub(1)..=(column + 1).min(columns - 1)).map(move |x| (x, y)))
+            {
+                let neighbor = (neighbor_row * columns + neighbor_column) as usize;
+                if !visited[neighbor] && gradients[neighbor] >= threshold {
+                    visited[neighbor] = true;
+                    stack.push(neighbor);
+                }
+            }
+        }
+
+        let (left, top) = (min_column.saturating_sub(1) * CANDIDATE_CELL_SIZE, min_row.saturating_sub(1) * CANDIDATE_CELL_SIZE);
+        let (right, bottom) = (((max_column + 2) * CANDIDATE_CELL_SIZE).min(*width), ((max_row + 2) * CANDIDATE_CELL_SIZE).min(*height));
+        regions.push((Rect { x: left, y: top, width: right - left, height: bottom - top }, total));
+    }
+
+    // Merge overlapping regions, so that a code split by a gap in its edges is decoded whole
+    let mut merged = true;
+    while merged {
+        merged = false;
+        'search: for first in 0..regions.len() {
+            for second in first + 1..regions.len() {
+                let (a, b) = (&regions[first].0, &regions[second].0);
+                if a.x < b.x + b.width && b.x < a.x + a.width && a.y < b.y + b.height && b.y < a.y + a.height {
+                    let (left, top) = (a.x.min(b.x), a.y.min(b.y));
+                    let (right, bottom) = ((a.x + a.width).max(b.x + b.width), (a.y + a.height).max(b.y + b.height));
+                    let (_, total) = regions.remove(second);
+                    regions[first] = (Rect { x: left, y: top, width: right - left, height: bottom - top }, regions[first].1 + total);
+                    merged = true;
+                    break 'search;
+                }
+            }
+        }
+    }
+
+    regions.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+    // rxing's global histogram fallback for smaller images panics on flat regions
+    regions.into_iter().map(|(region, _)| region).filter(|region| region.width >= MIN_CROP_SIZE && region.height >= MIN_CROP_SIZE).collect()
+}
+
+const MM_PER_INCH: f64 = 25.4;
+
+/// Estimates the module size by sampling the image along the line between the first two result points
+/// and averaging the narrowest runs of equal color, which correspond to single modules.
+fn estimate_module_size_px(result: &RXingResult, luma: &LumaImage) -> Option<f64> {
+    let points = result.getPoints();
+    if points.len() < 2 {
+        return None;
+    }
+
+    let samples = sample_line(luma, points[0], points[1]);
+    let (min, max) = samples.iter().fold((u8::MAX, u8::MIN), |(min, max), &v| (min.min(v), max.max(v)));
+    if max - min < 32 {
+        return None;
+    }
+    let threshold = ((min as u16 + max as u16) / 2) as u8;
+
+    let mut runs: Vec<u32> = Vec::new();
+    let mut current = samples[0] < threshold;
+    let mut length = 0;
+    for &sample in &samples {
+        if (sample < threshold) == current {
+            length += 1;
         } else {
-            None
+            runs.push(length);
+            current = !current;
+            length = 1;
         }
     }
+
+    // The first and last runs are usually cut off by the result points, so drop them
+    if runs.len() < 3 {
+        return None;
+    }
+    let mut runs = runs[1..].to_vec();
+    runs.sort_unstable();
+
+    let base = runs[runs.len() / 4] as f64;
+    let modules: Vec<f64> = runs.into_iter().map(|run| run as f64).filter(|&run| run >= base * 0.5 && run <= base * 1.5).collect();
+
+    Some(modules.iter().sum::<f64>() / modules.len() as f64)
 }
 
-fn process_multi_result<E>(results: Result<Vec<RXingResult>, E>) -> Option<Either<DecodeResult, Vec<DecodeResult>>> {
-    if let Ok(results) = results {
-        let results: Vec<DecodeResult> = results.into_iter().map(|x| x.into()).collect();
-        Some(Either::B(results))
+/// Combines the quality signals documented on `DecodeResult.trust` into a single score.
+fn compute_trust(result: &RXingResult, module_size_px: Option<f64>, luma: Option<&LumaImage>, assume_code39_check_digit: bool) -> f64 {
+    let format: JsBarcodeFormat = (*result.getBarcodeFormat()).into();
+    let mut signals: Vec<(f64, f64)> = Vec::new();
+
+    let has_check = match format {
+        JsBarcodeFormat::CODABAR | JsBarcodeFormat::ITF => false,
+        JsBarcodeFormat::Code39 => assume_code39_check_digit,
+        _ => true,
+    };
+    signals.push((0.4, if has_check { 1.0 } else { 0.5 }));
+
+    if let Some(module_size_px) = module_size_px {
+        signals.push((0.2, (module_size_px / 2.0).min(1.0)));
+    }
+
+    if format.is_linear() {
+        if let (Some(luma), Some(module_size_px)) = (luma, module_size_px) {
+            if let Some(quiet_zone) = quiet_zone_fraction(result.getPoints(), luma, module_size_px) {
+                signals.push((0.2, quiet_zone));
+            }
+        }
+    }
+
+    if let Some(RXingResultMetadataValue::ErrorCorrectionLevel(level)) = result.getRXingResultMetadata().get(&RXingResultMetadataType::ERROR_CORRECTION_LEVEL) {
+        let margin = match (format, level.as_str()) {
+            // rxing reports QR Code levels by their format bits (0 = M, 1 = L, 2 = H, 3 = Q)
+            (JsBarcodeFormat::QrCode, "1" | "L") => Some(0.25),
+            (JsBarcodeFormat::QrCode, "0" | "M") => Some(0.5),
+            (JsBarcodeFormat::QrCode, "3" | "Q") => Some(0.75),
+            (JsBarcodeFormat::QrCode, "2" | "H") => Some(1.0),
+            // PDF417 levels go from 0 to 8, anything from 4 up is plenty for a clean read
+            (JsBarcodeFormat::Pdf417, level) => level.parse::<f64>().ok().map(|level| (level / 4.0).min(1.0)),
+            _ => None,
+        };
+        if let Some(margin) = margin {
+            signals.push((0.2, margin));
+        }
+    }
+
+    let total_weight: f64 = signals.iter().map(|(weight, _)| weight).sum();
+    signals.iter().map(|(weight, value)| weight * value).sum::<f64>() / total_weight
+}
+
+/// Returns the fraction of light pixels in the 5 modules beyond each end of a 1D code, taking the worst side.
+fn quiet_zone_fraction(points: &[Point], luma: &LumaImage, module_size_px: f64) -> Option<f64> {
+    let (first, last) = (*points.first()?, *points.last()?);
+    let (dx, dy) = (last.getX() - first.getX(), last.getY() - first.getY());
+    let length = (dx * dx + dy * dy).sqrt();
+    if length < 1.0 {
+        return None;
+    }
+
+    let code = sample_line(luma, first, last);
+    let (min, max) = code.iter().fold((u8::MAX, u8::MIN), |(min, max), &v| (min.min(v), max.max(v)));
+    let threshold = (min as u16 + max as u16) / 2;
+
+    let reach = (module_size_px * 5.0) as f32;
+    let (ux, uy) = (dx / length * reach, dy / length * reach);
+    let (width, height) = (luma.1 as f32, luma.2 as f32);
+
+    let side = |from: Point, to: Point| {
+        // Pixels outside of the image count as missing quiet zone
+        let in_bounds = |point: Point| point.getX() >= 0.0 && point.getY() >= 0.0 && point.getX() < width && point.getY() < height;
+        let samples = sample_line(luma, from, to);
+        let light = samples.iter().filter(|&&sample| sample as u16 >= threshold).count() as f64;
+        let visible = if in_bounds(to) { 1.0 } else { 0.5 };
+        light / samples.len() as f64 * visible
+    };
+
+    let before = side(first, Point::new(first.getX() - ux, first.getY() - uy));
+    let after = side(last, Point::new(last.getX() + ux, last.getY() + uy));
+
+    Some(before.min(after))
+}
+
+/// The outline of `result`, clockwise from the corner closest to the top left of the image.
+fn compute_outline(result: &RXingResult, luma: Option<&LumaImage>) -> Vec<Point> {
+    let points = result.getPoints();
+    let format = *result.getBarcodeFormat();
+
+    let corners = if format == BarcodeFormat::QR_CODE && points.len() >= 3 {
+        qr_code_corners(result, points[0], points[1], points[2])
+    } else if JsBarcodeFormat::from(format).is_linear() && points.len() >= 2 {
+        linear_corners(points[0], points[points.len() - 1], luma)
     } else {
-        None
+        points.to_vec()
+    };
+
+    order_clockwise(convex_hull(corners))
+}
+
+/// The corners of a QR code from the centers of its finder patterns, which sit 3.5 modules inside each corner. The
+/// module count, needed to find the module size, is recovered from the number of data codewords and the error
+/// correction level. If it can't be, the outline runs through the finder pattern centers instead.
+fn qr_code_corners(result: &RXingResult, bottom_left: Point, top_left: Point, top_right: Point) -> Vec<Point> {
+    let bottom_right = top_right + bottom_left - top_left;
+
+    let Some(dimension) = qr_code_dimension(result) else {
+        return vec![top_left, top_right, bottom_right, bottom_left];
+    };
+    let modules_between_centers = dimension as f32 - 7.0;
+    let across = (top_right - top_left) / modules_between_centers * 3.5;
+    let down = (bottom_left - top_left) / modules_between_centers * 3.5;
+
+    vec![
+        top_left - across - down,
+        top_right + across - down,
+        bottom_right + across + down,
+        bottom_left - across + down,
+    ]
+}
+
+fn qr_code_dimension(result: &RXingResult) -> Option<u32> {
+    let Some(RXingResultMetadataValue::ErrorCorrectionLevel(level)) = result.getRXingResultMetadata().get(&RXingResultMetadataType::ERROR_CORRECTION_LEVEL) else {
+        return None;
+    };
+    let level: ErrorCorrectionLevel = level.parse().ok()?;
+    let data_codewords = result.getRawBytes().len() as u32;
+
+    (1..=40).find_map(|number| {
+        let version = Version::getVersionForNumber(number).ok()?;
+        let matches = version.getTotalCodewords() - version.getECBlocksForLevel(level).getTotalECCodewords() == data_codewords;
+        matches.then(|| version.getDimensionForVersion())
+    })
+}
+
+/// The corners of a 1D code, extending the scan line between `start` and `end` perpendicularly to the median extent
+/// of the bars crossing it.
+fn linear_corners(start: Point, end: Point, luma: Option<&LumaImage>) -> Vec<Point> {
+    let Some(luma) = luma else {
+        return vec![start, end];
+    };
+    let (pixels, width, height) = luma;
+    let length = start.distance(end);
+    if length < 1.0 {
+        return vec![start, end];
+    }
+
+    let samples = sample_line(luma, start, end);
+    let (darkest, lightest) = (samples.iter().min().copied().unwrap_or(0), samples.iter().max().copied().unwrap_or(0));
+    let threshold = (darkest as u32 + lightest as u32) / 2;
+    let is_dark = |point: Point| {
+        let (x, y) = (point.getX().round(), point.getY().round());
+        x >= 0.0 && y >= 0.0 && x < *width as f32 && y < *height as f32 && (pixels[(y as u32 * width + x as u32) as usize] as u32) < threshold
+    };
+
+    let direction = (end - start) / length;
+    let normal = Point::new(-direction.getY(), direction.getX());
+    let (mut above, mut below) = (Vec::new(), Vec::new());
+    for step in 1..32 {
+        let on_line = start + (end - start) * (step as f32 / 32.0);
+        if !is_dark(on_line) {
+            continue;
+        }
+
+        let extent = |sign: f32| (1..).take_while(|&offset| is_dark(on_line + normal * (sign * offset as f32))).count() as f32;
+        above.push(extent(-1.0));
+        below.push(extent(1.0));
     }
+    if above.is_empty() {
+        return vec![start, end];
+    }
+
+    let median = |mut extents: Vec<f32>| {
+        extents.sort_by(f32::total_cmp);
+        extents[extents.len() / 2]
+    };
+    let (above, below) = (normal * median(above), normal * median(below));
+
+    vec![start - above, end - above, end + below, start + below]
+}
+
+/// Convex hull of the points, without collinear points, using the monotone chain algorithm.
+fn convex_hull(mut points: Vec<Point>) -> Vec<Point> {
+    points.sort_by(|a, b| a.getX().total_cmp(&b.getX()).then(a.getY().total_cmp(&b.getY())));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+
+    let half_hull = |points: &mut dyn Iterator<Item = Point>| {
+        let mut hull: Vec<Point> = Vec::new();
+        for point in points {
+            while hull.len() >= 2 && (hull[hull.len() - 1] - hull[hull.len() - 2]).cross(point - hull[hull.len() - 2]) <= 0.0 {
+                hull.pop();
+            }
+            hull.push(point);
+        }
+        // The last point of each half is the first point of the other
+        hull.pop();
+        hull
+    };
+
+    let mut hull = half_hull(&mut points.iter().copied());
+    hull.extend(half_hull(&mut points.iter().rev().copied()));
+    hull
+}
+
+/// Orders the points clockwise, as seen in an image whose y axis points down, starting from the one closest to the
+/// top left of the image.
+fn order_clockwise(mut points: Vec<Point>) -> Vec<Point> {
+    if points.is_empty() {
+        return points;
+    }
+
+    let center = points.iter().sum::<Point>() / points.len() as f32;
+    points.sort_by(|a, b| {
+        let angle = |point: &Point| (point.getY() - center.getY()).atan2(point.getX() - center.getX());
+        angle(a).total_cmp(&angle(b))
+    });
+
+    let first = points.iter().enumerate()
+        .min_by(|(_, a), (_, b)| (a.getX() + a.getY()).total_cmp(&(b.getX() + b.getY())))
+        .map_or(0, |(index, _)| index);
+    points.rotate_left(first);
+
+    points
+}
+
+/// Samples the luma image at one pixel intervals along the line from `from` to `to`.
+fn sample_line(luma: &LumaImage, from: Point, to: Point) -> Vec<u8> {
+    let (pixels, width, height) = luma;
+    let (dx, dy) = (to.getX() - from.getX(), to.getY() - from.getY());
+    let steps = (dx * dx + dy * dy).sqrt().ceil().max(1.0) as u32;
+
+    (0..=steps).map(|step| {
+        let t = step as f32 / steps as f32;
+        let x = (from.getX() + dx * t).round().clamp(0.0, (*width - 1) as f32) as u32;
+        let y = (from.getY() + dy * t).round().clamp(0.0, (*height - 1) as f32) as u32;
+        pixels[(y * width + x) as usize]
+    }).collect()
 }
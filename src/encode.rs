@@ -1,15 +1,32 @@
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::io::{Cursor, Write};
 
-use image::DynamicImage;
-use napi::bindgen_prelude::Buffer;
-use rxing::{EncodeHintType, EncodeHintValue, EncodingHintDictionary, MultiFormatWriter, Writer};
+use image::imageops::FilterType;
+use image::{DynamicImage, GenericImage, GenericImageView, GrayImage, Luma, Rgb, RgbImage, Rgba};
+use napi::bindgen_prelude::{Buffer, FromNapiValue, ToNapiValue};
+use napi::{Env, JsError, JsFunction, JsObject, JsUnknown, NapiRaw, NapiValue, Status};
+use rxing::common::BitMatrix;
+use rxing::{BarcodeFormat, DecodeHintType, DecodeHintValue, DecodingHintDictionary, EncodeHintType, EncodeHintValue, EncodingHintDictionary, MultiFormatWriter, Writer};
 
+use crate::color::{contrast_ratio, is_scannable, parse_color, relative_luminance, MIN_CONTRAST_RATIO, WHITE};
 use crate::JsBarcodeFormat;
 
+/**
+ * A rectangular region of an image, in pixels
+ */
 #[napi(object)]
-#[derive(Default)]
+#[derive(Clone)]
+pub struct Rect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[napi(object)]
+#[derive(Clone, Default)]
 pub struct EncodeOptions {
     pub barcode_format: Option<JsBarcodeFormat>,
     pub width: Option<u32>,
@@ -30,8 +47,131 @@ pub struct EncodeOptions {
     pub force_c40: Option<bool>,
     pub code128_compact: Option<bool>,
     pub output_file: Option<String>,
+    /** Pixel regions to leave blank in the final image, e.g. for variable data printed later */
+    pub knockout: Option<Vec<Rect>>,
+    /** Decode the rendered image and only return it if it reads back as `data` */
+    pub verify: Option<bool>,
+    /**
+     * Decode a copy of the rendered image degraded as by printing (dot gain, blur and noise, in proportion to the
+     * module size), and emit a warning if it no longer reads back as `data`. The image is returned either way, and
+     * `encodeWithMetadata` reports the outcome as `degradationPassed`
+     */
+    pub simulate_degradation: Option<bool>,
+    /**
+     * Output the code flush to its edges, without any quiet zone, overriding `margin`.
+     * The result will not scan on its own, which is warned about for formats that require a quiet zone: it is meant
+     * to be composited into a design that supplies its own quiet zone
+     */
+    pub no_quiet_zone: Option<bool>,
+    /**
+     * Pick the smallest margin that still decodes, overriding `margin`: starting from the quiet zone required by the
+     * format's specification, or from none if `allowNarrowQuietZone` is set, the margin is grown until the rendered
+     * image reads back as `data`, up to three times the specified quiet zone. Encoding fails if that doesn't decode
+     * either, and when `noQuietZone` is set. Use `encodeWithMetadata` to get the chosen margin
+     */
+    pub auto_margin: Option<bool>,
+    /** Let `autoMargin` try margins narrower than the quiet zone required by the format's specification */
+    pub allow_narrow_quiet_zone: Option<bool>,
+    /**
+     * Make `encodeWithMetadata` also return the module matrix of the code, as `matrix`, to render it again later
+     * without encoding the data a second time
+     */
+    pub include_matrix: Option<bool>,
+    /** Image format of the output, either `"jpeg"` (the default) or `"png"` */
+    pub output_format: Option<String>,
+    /**
+     * Store the encoded data and barcode format in the image, as `Barcode Data` and `Barcode Format` PNG text
+     * chunks. This always produces a PNG, whatever `outputFormat` is set to
+     */
+    pub embed_payload_metadata: Option<bool>,
+    /**
+     * Foreground colors, as `#rrggbb` hex strings, cycled through for each row of modules. Every color must be
+     * darker than the white background with a contrast ratio of at least 3:1, or encoding fails unless
+     * `allowLowContrast` is set, and the result is decoded to check it still scans
+     */
+    pub row_colors: Option<Vec<String>>,
+    /**
+     * An image (PNG, JPEG, ...) scaled to cover the whole code and used as its background, with the modules drawn
+     * over it. Encoding fails when more than 5% of the background lacks a 3:1 contrast ratio with the modules,
+     * unless `allowLowContrast` is set, and the result is decoded to check it still scans
+     */
+    pub background_image: Option<Buffer>,
+    /**
+     * How to handle an odd number of digits for ITF, which can only encode digit pairs: `"none"` (the default)
+     * fails with a warning, `"leading-zero"` prepends a zero. Other values fail with a warning, whatever the digit count
+     */
+    pub itf_padding: Option<String>,
+    /**
+     * Color of the three finder patterns of a QR code, as a `#rrggbb` hex string, the other modules keeping their
+     * color. Like `rowColors` it must have a contrast ratio of at least 3:1 with white. The result is decoded to
+     * check it still scans, and encoding fails for other formats
+     */
+    pub finder_color: Option<String>,
+    /**
+     * Opacity of the modules, from 0 to 1, for faint watermark-like codes: the modules are blended with the white
+     * background or `backgroundImage` under them. Like `rowColors`, the lightest modules must keep a contrast ratio
+     * of at least 3:1 with white, or encoding fails unless `allowLowContrast` is set. The result is always decoded to
+     * check it still scans
+     */
+    pub module_opacity: Option<f64>,
+    /**
+     * A character standing for FNC1 in `data`, for GS1 Data Matrix codes with several application identifiers,
+     * e.g. `"|"` to encode `"0109501101020917|10ABC"`. Every occurrence is encoded as an FNC1 separator, which
+     * decodes as a GS character (`\x1D`), and a leading FNC1 is always added, so a placeholder at the start of the
+     * data is optional. This uses a simpler encoder, ignoring `dataMatrixCompact`, `forceC40` and `gs1Format`, and
+     * encoding fails for other formats
+     */
+    pub fnc1_placeholder: Option<String>,
+    /**
+     * Render colors that are unlikely to scan (`rowColors`, `finderColor` or a `backgroundImage` with less than a
+     * 3:1 contrast ratio) with a warning instead of failing. This also skips decoding `rowColors`, `finderColor`
+     * and `backgroundImage` results to check they scan, unless `verify` is set
+     */
+    pub allow_low_contrast: Option<bool>,
+}
+
+/**
+ * An encoded barcode, along with details about how it was encoded
+ */
+#[napi(object)]
+pub struct EncodeResult {
+    /** The encoded image, as `encode` returns it */
+    pub image: Buffer,
+    /** The margin the code was encoded with, which `autoMargin` may have picked */
+    pub margin: u32,
+    /**
+     * The module matrix of the code, if `includeMatrix` is set: one cell per module, without scaling or quiet zone,
+     * e.g. 21x21 for a version 1 QR code. Linear codes are a single row of bars and spaces, and PDF417 codes a row
+     * per row of codewords. Image level options such as `rowColors` or `knockout` are left out
+     */
+    pub matrix: Option<CodeMatrix>,
+    /** Whether the degraded copy still read back as `data`, if `simulateDegradation` is set */
+    pub degradation_passed: Option<bool>,
 }
 
+/**
+ * The modules of an encoded code, row by row, `true` being dark
+ */
+#[napi(object)]
+pub struct CodeMatrix {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<bool>,
+}
+
+impl From<&BitMatrix> for CodeMatrix {
+    fn from(value: &BitMatrix) -> Self {
+        let (width, height) = (value.getWidth(), value.getHeight());
+        CodeMatrix { width, height, data: (0..height).flat_map(|y| (0..width).map(move |x| value.get(x, y))).collect() }
+    }
+}
+
+/// Replaces the modules of the code before they are rasterized, returning `None` if the callback failed.
+type TransformFn<'a> = &'a dyn Fn(&BitMatrix) -> Option<CodeMatrix>;
+
+/// Fraction of the background allowed to lack contrast with the modules before warning about it.
+const MAX_LOW_CONTRAST_FRACTION: f64 = 0.05;
+
 /**
  * Encode a barcode from a string, returning a buffer representing the image
  *
@@ -49,10 +189,225 @@ pub struct EncodeOptions {
  * fs.writeFileSync('hello-world.png', buffer);
  */
 #[napi]
-pub fn encode(data: String, options: Option<EncodeOptions>) -> Option<Buffer> {
+pub fn encode(env: Env, data: String, options: Option<EncodeOptions>) -> Option<Buffer> {
+    let (bytes, _) = encode_image(&env, &data, &options.unwrap_or_default(), None)?;
+    Some(Buffer::from(bytes))
+}
+
+/**
+ * Encode a barcode from a string like `encode`, letting a callback change its modules before they are rendered
+ *
+ * The callback is called with the module matrix of the code, one cell per module without any quiet zone (a single
+ * row for linear codes), and must return a matrix of the same size, e.g. to draw a custom marker. The returned
+ * modules are then scaled and padded to the requested size like the original ones. It is called again for every
+ * margin `autoMargin` tries. The result is decoded to check it still scans, and encoding fails with a warning if it
+ * doesn't.
+ *
+ * @param {string} data The data to encode
+ * @param {(matrix: CodeMatrix) => CodeMatrix} transform The callback changing the matrix
+ * @param {EncodeOptions} [options] Optional options to pass to the encoder
+ *
+ * @returns {Buffer|null} A buffer representing the encoded barcode, or `null` if the barcode could not be encoded or encountered an error
+ *
+ * @example
+ * const { encodeWithTransform } = require('@rxing/rxing');
+ *
+ * const buffer = encodeWithTransform('Hello World!', ({ width, height, data }) => {
+ *   // Flip the center module, which error correction recovers from
+ *   const center = Math.floor(height / 2) * width + Math.floor(width / 2);
+ *   data[center] = !data[center];
+ *   return { width, height, data };
+ * });
+ */
+#[napi(ts_args_type = "data: string, transform: (matrix: CodeMatrix) => CodeMatrix, options?: EncodeOptions | undefined | null")]
+pub fn encode_with_transform(env: Env, data: String, transform: JsFunction, options: Option<EncodeOptions>) -> Option<Buffer> {
+    let transform = |bit_matrix: &BitMatrix| match call_transform(&env, &transform, bit_matrix.into()) {
+        Ok(matrix) => Some(matrix),
+        Err(error) if error.status == Status::PendingException => None,
+        Err(_) => {
+            emit_warning(&env, "transform must return a { width, height, data } matrix");
+            None
+        }
+    };
+
+    let (bytes, _) = encode_image(&env, &data, &options.unwrap_or_default(), Some(&transform))?;
+    Some(Buffer::from(bytes))
+}
+
+/// Calls the `transform` callback of `encodeWithTransform`. An exception it throws is thrown again, to reach the
+/// caller once encoding gives up.
+fn call_transform(env: &Env, transform: &JsFunction, matrix: CodeMatrix) -> napi::Result<CodeMatrix> {
+    let matrix = unsafe { JsUnknown::from_raw_unchecked(env.raw(), CodeMatrix::to_napi_value(env.raw(), matrix)?) };
+    let transformed = transform.call(None, &[matrix]).map_err(|error| {
+        unsafe { JsError::from(error).throw_into(env.raw()) };
+        napi::Error::from_status(Status::PendingException)
+    })?;
+    unsafe { CodeMatrix::from_napi_value(env.raw(), transformed.raw()) }
+}
+
+/**
+ * Encode a barcode from a string like `encode`, also returning details about how it was encoded
+ *
+ * @param {string} data The data to encode
+ * @param {EncodeOptions} [options] Optional options to pass to the encoder
+ *
+ * @returns {EncodeResult|null} The encoded image and its details, or `null` if the barcode could not be encoded or encountered an error
+ *
+ * @example
+ * const { encodeWithMetadata } = require('@rxing/rxing');
+ *
+ * const { image, margin } = encodeWithMetadata('Hello World!', { autoMargin: true });
+ */
+#[napi]
+pub fn encode_with_metadata(env: Env, data: String, options: Option<EncodeOptions>) -> Option<EncodeResult> {
     let options = options.unwrap_or_default();
-    let mut hints: EncodingHintDictionary = HashMap::new();
+    let (bytes, rendering) = encode_image(&env, &data, &options, None)?;
+    let matrix = rendering.modules.as_ref().map(CodeMatrix::from);
+    Some(EncodeResult { image: Buffer::from(bytes), margin: rendering.margin, matrix, degradation_passed: rendering.degradation_passed })
+}
+
+/// Renders and writes the image of `encode`, saving it to `outputFile` if set, and returns it along with the
+/// rendering it was written from.
+fn encode_image(env: &Env, data: &str, options: &EncodeOptions, transform: Option<TransformFn>) -> Option<(Vec<u8>, Rendering)> {
+    let rendering = render(data, options, transform, &mut |warning| emit_warning(env, warning))?;
+    let image = &rendering.image;
+
+    let bytes = if options.embed_payload_metadata.unwrap_or(false) {
+        let barcode_format: BarcodeFormat = options.barcode_format.unwrap_or(JsBarcodeFormat::QrCode).into();
+        write_png(image, &[("Barcode Data", data), ("Barcode Format", &barcode_format.to_string())])?
+    } else {
+        write_image(image, options.output_format.as_deref())?
+    };
+
+    if let Some(file_path) = &options.output_file {
+        write_to_file(file_path, &bytes).ok()?;
+    }
+
+    Some((bytes, rendering))
+}
+
+/**
+ * Estimate the size in bytes of the image `encode` would return for the same arguments, without rendering it.
+ * This is a planning figure rather than an exact size: it models the output of a plain black and white code, so
+ * options styling the image, like `rowColors` or `backgroundImage`, are not accounted for, and neither is
+ * `autoMargin`, the estimate using `margin` instead
+ *
+ * @param {string} data The data to encode
+ * @param {EncodeOptions} [options] Optional options to pass to the encoder
+ *
+ * @returns {number|null} The estimated size in bytes, or `null` if the barcode could not be encoded
+ *
+ * @example
+ * const { estimateEncodedSize } = require('@rxing/rxing');
+ *
+ * const bytes = estimateEncodedSize('Hello World!', { width: 400, outputFormat: 'png' });
+ */
+#[napi]
+pub fn estimate_encoded_size(env: Env, data: String, options: Option<EncodeOptions>) -> Option<u32> {
+    let options = options.unwrap_or_default();
+    let (_, bit_matrix) = encode_matrix(&data, &options, configured_margin(&options), &mut |warning| emit_warning(&env, warning))?;
+    let estimate = if options.embed_payload_metadata.unwrap_or(false) {
+        // The two text chunks, with 12 bytes of framing each
+        let barcode_format: BarcodeFormat = options.barcode_format.unwrap_or(JsBarcodeFormat::QrCode).into();
+        let text_chunks = "Barcode Data".len() + data.len() + "Barcode Format".len() + barcode_format.to_string().len() + 2 * 12;
+        estimate_rgb_png_size(&bit_matrix) + text_chunks as f64
+    } else {
+        estimate_image_size(&bit_matrix, options.output_format.as_deref())?
+    };
 
+    Some(estimate.round() as u32)
+}
+
+/// Approximate size of a black and white image of `bit_matrix` in the given output format, from a linear model of
+/// its content fitted to the output of `encode`: JPEG mostly pays for the 8x8 blocks crossed by edges, more so when
+/// they are crossed both ways, while PNG mostly pays for each row and for the edges of rows that differ from the
+/// one above.
+fn estimate_image_size(bit_matrix: &BitMatrix, output_format: Option<&str>) -> Option<f64> {
+    let (width, height) = (bit_matrix.getWidth(), bit_matrix.getHeight());
+
+    match output_format.unwrap_or("jpeg") {
+        "jpeg" | "jpg" => {
+            let (mut flat_blocks, mut one_way_blocks, mut two_way_blocks) = (0, 0, 0);
+            for block_y in (0..height).step_by(8) {
+                for block_x in (0..width).step_by(8) {
+                    let (x_end, y_end) = ((block_x + 8).min(width), (block_y + 8).min(height));
+                    let horizontal_edge = (block_y..y_end).any(|y| (block_x + 1..x_end).any(|x| bit_matrix.get(x, y) != bit_matrix.get(x - 1, y)));
+                    let vertical_edge = (block_y + 1..y_end).any(|y| (block_x..x_end).any(|x| bit_matrix.get(x, y) != bit_matrix.get(x, y - 1)));
+                    match (horizontal_edge, vertical_edge) {
+                        (false, false) => flat_blocks += 1,
+                        (true, true) => two_way_blocks += 1,
+                        _ => one_way_blocks += 1,
+                    }
+                }
+            }
+
+            Some(330.0 + 2.0 * flat_blocks as f64 + 17.4 * one_way_blocks as f64 + 92.0 * two_way_blocks as f64)
+        }
+        "png" => {
+            let mut edges = 0;
+            for y in 0..height {
+                if y > 0 && (0..width).all(|x| bit_matrix.get(x, y) == bit_matrix.get(x, y - 1)) {
+                    continue;
+                }
+                edges += (1..width).filter(|&x| bit_matrix.get(x, y) != bit_matrix.get(x - 1, y)).count();
+            }
+
+            Some(70.0 + 4.7 * height as f64 + 1.05 * edges as f64 + 0.015 * (width * height) as f64)
+        }
+        _ => None,
+    }
+}
+
+/// Approximate size of the RGB PNG written by `write_png`, whose default compression settings make every row pay for
+/// its edges, even when it repeats the one above.
+fn estimate_rgb_png_size(bit_matrix: &BitMatrix) -> f64 {
+    let (width, height) = (bit_matrix.getWidth(), bit_matrix.getHeight());
+    let edges = (0..height).map(|y| (1..width).filter(|&x| bit_matrix.get(x, y) != bit_matrix.get(x - 1, y)).count()).sum::<usize>();
+
+    230.0 + 0.84 * edges as f64 + 0.045 * (width * height) as f64
+}
+
+/// Serializes the image in the given output format, defaulting to JPEG.
+fn write_image(image: &DynamicImage, output_format: Option<&str>) -> Option<Vec<u8>> {
+    let output_format = match output_format.unwrap_or("jpeg") {
+        "jpeg" | "jpg" => image::ImageOutputFormat::Jpeg(100),
+        "png" => image::ImageOutputFormat::Png,
+        _ => return None,
+    };
+
+    let mut bytes: Vec<u8> = Vec::new();
+    image.write_to(&mut Cursor::new(&mut bytes), output_format).ok()?;
+
+    Some(bytes)
+}
+
+/// Writes the image as an RGB PNG with the given text chunks, using `iTXt` for text that isn't Latin-1.
+fn write_png(image: &DynamicImage, text_chunks: &[(&str, &str)]) -> Option<Vec<u8>> {
+    let image = image.to_rgb8();
+    let mut bytes: Vec<u8> = Vec::new();
+
+    let mut encoder = png::Encoder::new(&mut bytes, image.width(), image.height());
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    for (keyword, text) in text_chunks {
+        let added = if text.chars().all(|c| (c as u32) <= 0xFF) {
+            encoder.add_text_chunk(keyword.to_string(), text.to_string())
+        } else {
+            encoder.add_itxt_chunk(keyword.to_string(), text.to_string())
+        };
+        added.ok()?;
+    }
+
+    let mut writer = encoder.write_header().ok()?;
+    writer.write_image_data(image.as_raw()).ok()?;
+    writer.finish().ok()?;
+
+    Some(bytes)
+}
+
+/// Encodes `data` as a bit matrix with the given margin, applying the options that change what gets encoded, and
+/// returns it along with the data actually encoded.
+fn encode_matrix<'a>(data: &'a str, options: &EncodeOptions, margin: u32, warn: &mut dyn FnMut(&str)) -> Option<(Cow<'a, str>, BitMatrix)> {
     let barcode_format = options.barcode_format.unwrap_or(JsBarcodeFormat::QrCode);
     let width = options.width.unwrap_or(200);
     let height = options.height.unwrap_or_else(|| if barcode_format == JsBarcodeFormat::QrCode{
@@ -60,16 +415,321 @@ pub fn encode(data: String, options: Option<EncodeOptions>) -> Option<Buffer> {
     } else {
         200
     });
-    let margin = options.margin.unwrap_or(0);
+    let hints = create_hints(options, margin);
+
+    let data = match (barcode_format, &options.fnc1_placeholder) {
+        (JsBarcodeFormat::DataMatrix, Some(fnc1_placeholder)) => Cow::Owned(replace_fnc1_placeholder(data, fnc1_placeholder)?),
+        (_, Some(_)) => return None,
+        (JsBarcodeFormat::ITF, None) => pad_itf_data(data, options.itf_padding.as_deref(), warn)?,
+        _ => Cow::Borrowed(data),
+    };
+
+    let writer = MultiFormatWriter;
+    let bit_matrix = if options.fnc1_placeholder.is_some() {
+        crate::datamatrix::encode_gs1(&data, width, height)?
+    } else {
+        writer.encode_with_hints(
+            &data,
+            &barcode_format.into(),
+            width as i32,
+            height as i32,
+            &hints,
+        ).ok()?
+    };
+
+    // Some writers always add a quiet zone, whatever the margin hint says
+    let bit_matrix = if options.no_quiet_zone.unwrap_or(false) {
+        if spec_quiet_zone(barcode_format, &bit_matrix) > 0 {
+            warn("noQuietZone crops the code below the quiet zone its specification requires, it will not scan until it is placed on one");
+        }
+        crop_quiet_zone(bit_matrix)
+    } else {
+        bit_matrix
+    };
+    Some((data, bit_matrix))
+}
+
+/// Height of the rows of PDF417 codes drawn by rxing's writer at their smallest, in modules.
+const PDF417_ROW_HEIGHT: u32 = 4;
+
+/// Encodes `data` like `encode_matrix`, as one cell per module without scaling or quiet zone. Linear codes are a
+/// single row.
+fn encode_modules(data: &str, options: &EncodeOptions) -> Option<BitMatrix> {
+    // The writers draw codes at their smallest when asked for no size at all
+    let smallest = EncodeOptions { width: Some(0), height: Some(0), no_quiet_zone: None, ..options.clone() };
+    let (_, bit_matrix) = encode_matrix(data, &smallest, 0, &mut |_| {})?;
+    if options.barcode_format != Some(JsBarcodeFormat::Pdf417) {
+        return Some(bit_matrix);
+    }
+
+    // PDF417 rows are drawn several modules high, keep one module of each
+    let (width, height) = (bit_matrix.getWidth(), bit_matrix.getHeight() / PDF417_ROW_HEIGHT);
+    let mut modules = BitMatrix::new(width, height).ok()?;
+    for y in 0..height {
+        for x in 0..width {
+            if bit_matrix.get(x, y * PDF417_ROW_HEIGHT) {
+                modules.set(x, y);
+            }
+        }
+    }
+
+    Some(modules)
+}
+
+/// Rasterizes `modules` the way the writer rasterized `original` into `bit_matrix`. The writers scale modules by a
+/// whole number of pixels and pad the code around them, so the dark areas of both matrices give the layout.
+fn scale_modules(modules: &BitMatrix, original: &BitMatrix, bit_matrix: &BitMatrix) -> Option<BitMatrix> {
+    let [left, top, width, height] = original.getEnclosingRectangle()?;
+    let [scaled_left, scaled_top, scaled_width, scaled_height] = bit_matrix.getEnclosingRectangle()?;
+    let (scale_x, scale_y) = ((scaled_width / width).max(1) as i64, (scaled_height / height).max(1) as i64);
+    let origin_x = scaled_left as i64 - left as i64 * scale_x;
+    let origin_y = scaled_top as i64 - top as i64 * scale_y;
+
+    let mut scaled = BitMatrix::new(bit_matrix.getWidth(), bit_matrix.getHeight()).ok()?;
+    for y in 0..scaled.getHeight() {
+        let module_y = (y as i64 - origin_y).div_euclid(scale_y);
+        if !(0..modules.getHeight() as i64).contains(&module_y) {
+            continue;
+        }
+        for x in 0..scaled.getWidth() {
+            let module_x = (x as i64 - origin_x).div_euclid(scale_x);
+            if (0..modules.getWidth() as i64).contains(&module_x) && modules.get(module_x as u32, module_y as u32) {
+                scaled.set(x, y);
+            }
+        }
+    }
+
+    Some(scaled)
+}
+
+/// A code rendered by `render`.
+pub(crate) struct Rendering {
+    pub image: DynamicImage,
+    /// The margin it was encoded with
+    pub margin: u32,
+    /// Its modules as drawn, one cell per module, when `includeMatrix` or a transform needed them
+    pub modules: Option<BitMatrix>,
+    /// Whether it survived `simulateDegradation`, if set
+    pub degradation_passed: Option<bool>,
+}
+
+/// Encodes `data` and rasterizes it, applying `transform` to the encoded matrix and all the image level options.
+/// Problems that don't prevent rendering, but may prevent scanning, are reported to `warn`.
+pub(crate) fn render(data: &str, options: &EncodeOptions, transform: Option<TransformFn>, warn: &mut dyn FnMut(&str)) -> Option<Rendering> {
+    if !options.auto_margin.unwrap_or(false) {
+        return render_with_margin(data, options, configured_margin(options), transform, warn);
+    }
+    if options.no_quiet_zone.unwrap_or(false) {
+        return None;
+    }
+
+    // The quiet zone is sized from the code itself for some formats
+    let barcode_format = options.barcode_format.unwrap_or(JsBarcodeFormat::QrCode);
+    let (_, bit_matrix) = encode_matrix(data, options, 0, &mut |_| {})?;
+    let spec_margin = spec_quiet_zone(barcode_format, &bit_matrix);
+    let min_margin = if options.allow_narrow_quiet_zone.unwrap_or(false) { 0 } else { spec_margin };
+
+    // Only the warnings of the rendering that gets returned are reported, rather than once per margin tried, or
+    // those of the narrowest margin when none decodes
+    let mut warnings = None;
+    for margin in min_margin..=spec_margin * AUTO_MARGIN_MAX_FACTOR {
+        let mut margin_warnings = Vec::new();
+        let rendered = render_with_margin(data, options, margin, transform, &mut |warning| margin_warnings.push(warning.to_string()));
+        if rendered.is_some() {
+            margin_warnings.iter().for_each(|warning| warn(warning));
+            return rendered;
+        }
+        warnings.get_or_insert(margin_warnings);
+    }
+    warnings.unwrap_or_default().iter().for_each(|warning| warn(warning));
+
+    None
+}
+
+/// Widest margin `autoMargin` tries, as a multiple of the quiet zone required by the format's specification.
+const AUTO_MARGIN_MAX_FACTOR: u32 = 3;
+
+/// The margin set by the options, ignoring `autoMargin`.
+fn configured_margin(options: &EncodeOptions) -> u32 {
+    if options.no_quiet_zone.unwrap_or(false) { 0 } else { options.margin.unwrap_or(0) }
+}
+
+/// Quiet zone required by the specification of `barcode_format`, in the units of rxing's margin hint: modules on
+/// each side for QR codes, modules over both sides for linear codes, and pixels on each side for PDF417, measured
+/// on the start pattern of `bit_matrix`. The other writers ignore the margin hint.
+fn spec_quiet_zone(barcode_format: JsBarcodeFormat, bit_matrix: &BitMatrix) -> u32 {
+    match barcode_format {
+        JsBarcodeFormat::QrCode => 4,
+        JsBarcodeFormat::Ean8 => 7 + 7,
+        JsBarcodeFormat::UpcE => 9 + 7,
+        JsBarcodeFormat::Ean13 => 11 + 7,
+        JsBarcodeFormat::UpcA => 9 + 9,
+        JsBarcodeFormat::CODABAR | JsBarcodeFormat::Code39 | JsBarcodeFormat::Code93 | JsBarcodeFormat::Code128 | JsBarcodeFormat::ITF => 10 + 10,
+        JsBarcodeFormat::Pdf417 => {
+            // The start pattern begins with a bar 8 modules wide, running along the first row, or down the first
+            // column when the writer rotated the code
+            let first_run = |pixels: &mut dyn Iterator<Item = bool>| pixels.take_while(|&dark| dark).count() as u32;
+            let row = first_run(&mut (0..bit_matrix.getWidth()).map(|x| bit_matrix.get(x, 0)));
+            let column = first_run(&mut (0..bit_matrix.getHeight()).map(|y| bit_matrix.get(0, y)));
+            2 * (row.min(column) / 8).max(1)
+        }
+        _ => 0,
+    }
+}
+
+/// Renders `data` like `render`, with the given margin.
+fn render_with_margin(data: &str, options: &EncodeOptions, margin: u32, transform: Option<TransformFn>, warn: &mut dyn FnMut(&str)) -> Option<Rendering> {
+    let barcode_format = options.barcode_format.unwrap_or(JsBarcodeFormat::QrCode);
+    let (encoded, bit_matrix) = encode_matrix(data, options, margin, warn)?;
+    let needs_modules = transform.is_some() || options.include_matrix.unwrap_or(false);
+    let modules = if needs_modules { Some(encode_modules(data, options)?) } else { None };
+    let (bit_matrix, modules) = match (transform, modules) {
+        (Some(transform), Some(modules)) => {
+            let transformed = apply_transform(transform, &modules, warn)?;
+            (scale_modules(&transformed, &modules, &bit_matrix)?, Some(transformed))
+        }
+        (_, modules) => (bit_matrix, modules),
+    };
+    let data = encoded.as_ref();
+
+    let allow_low_contrast = options.allow_low_contrast.unwrap_or(false);
+    let foreground_colors = match &options.row_colors {
+        Some(row_colors) => Some(parse_foreground_colors(row_colors, "rowColors", allow_low_contrast, warn)?),
+        None => None,
+    };
+    let mut image: DynamicImage = match &foreground_colors {
+        Some(foreground_colors) => render_row_colors(&bit_matrix, foreground_colors),
+        None => bit_matrix.clone().into(),
+    };
+    let mut foreground_colors = foreground_colors.unwrap_or_else(|| vec![Rgb([0, 0, 0])]);
+
+    if let Some(finder_color) = &options.finder_color {
+        if barcode_format != JsBarcodeFormat::QrCode {
+            return None;
+        }
+        let finder_color = parse_foreground_colors(std::slice::from_ref(finder_color), "finderColor", allow_low_contrast, warn)?[0];
+        color_finder_patterns(&mut image, &bit_matrix, finder_color)?;
+        foreground_colors.push(finder_color);
+    }
+
+    if let Some(opacity) = options.module_opacity {
+        if !(0.0..=1.0).contains(&opacity) {
+            return None;
+        }
+        let lightest = blend_color(lightest_color(&foreground_colors), WHITE, opacity);
+        if !is_scannable(lightest, WHITE) {
+            let problem = format!(
+                "moduleOpacity {opacity} makes the modules too faint against a white background (contrast ratio {:.1}:1, at least {MIN_CONTRAST_RATIO}:1 is needed)",
+                contrast_ratio(lightest, WHITE)
+            );
+            check_contrast(&problem, allow_low_contrast, warn)?;
+        }
+    }
+
+    let background = match &options.background_image {
+        Some(background_image) => {
+            let background = image::load_from_memory(background_image).ok()?
+                .resize_to_fill(image.width(), image.height(), FilterType::Triangle)
+                .to_rgb8();
+            Some(background)
+        }
+        None => None,
+    };
+    if let Some(background) = &background {
+        let low_contrast = apply_background(&mut image, &bit_matrix, background, &foreground_colors);
+        if low_contrast > MAX_LOW_CONTRAST_FRACTION {
+            let problem = format!("backgroundImage lacks a 3:1 contrast ratio with the modules over {:.0}% of the code", low_contrast * 100.0);
+            check_contrast(&problem, allow_low_contrast, warn)?;
+        }
+    }
+
+    if let Some(opacity) = options.module_opacity {
+        apply_module_opacity(&mut image, &bit_matrix, background.as_ref(), opacity);
+    }
+
+    if let Some(knockout) = &options.knockout {
+        apply_knockout(&mut image, knockout);
+    }
+
+    let styled = options.background_image.is_some() || options.finder_color.is_some() || options.row_colors.is_some();
+    let always_verify = options.verify.unwrap_or(false) || options.auto_margin.unwrap_or(false) || options.module_opacity.is_some() || transform.is_some();
+    let verify = always_verify || (styled && !allow_low_contrast);
+    if verify && !verify_image(&image, data, barcode_format.into()) {
+        if transform.is_some() {
+            warn("the matrix returned by transform no longer decodes as the encoded data");
+        }
+        return None;
+    }
+
+    let degradation_passed = options.simulate_degradation.unwrap_or(false).then(|| verify_image(&simulate_print_degradation(&image), data, barcode_format.into()));
+    if degradation_passed == Some(false) {
+        warn("the code no longer decodes once degraded by simulated dot gain, blur and noise, it may not survive printing");
+    }
+
+    Some(Rendering { image, margin, modules, degradation_passed })
+}
+
+/// Replaces the modules with the matrix returned by `transform`, which must keep their size.
+fn apply_transform(transform: TransformFn, bit_matrix: &BitMatrix, warn: &mut dyn FnMut(&str)) -> Option<BitMatrix> {
+    let (width, height) = (bit_matrix.getWidth(), bit_matrix.getHeight());
+    let matrix = transform(bit_matrix)?;
+    if matrix.width != width || matrix.height != height || matrix.data.len() != (width * height) as usize {
+        warn(&format!("the matrix returned by transform must keep its size of {width}x{height}"));
+        return None;
+    }
+
+    let mut transformed = BitMatrix::new(width, height).ok()?;
+    for (index, _) in matrix.data.iter().enumerate().filter(|(_, &dark)| dark) {
+        transformed.set(index as u32 % width, index as u32 / width);
+    }
+
+    Some(transformed)
+}
+
+/// Makes the digit count of ITF data even according to `itf_padding`.
+fn pad_itf_data<'a>(data: &'a str, itf_padding: Option<&str>, warn: &mut dyn FnMut(&str)) -> Option<Cow<'a, str>> {
+    let itf_padding = itf_padding.unwrap_or("none");
+    if !matches!(itf_padding, "none" | "leading-zero") {
+        warn(&format!("itfPadding must be \"none\" or \"leading-zero\", got \"{itf_padding}\""));
+        return None;
+    }
+    if data.len().is_multiple_of(2) {
+        return Some(Cow::Borrowed(data));
+    }
+
+    if itf_padding == "leading-zero" {
+        return Some(Cow::Owned(format!("0{data}")));
+    }
+    warn(&format!(
+        "ITF requires an even number of digits but got {}, set itfPadding to \"leading-zero\" to pad it",
+        data.len()
+    ));
+    None
+}
+
+/// Replaces the FNC1 placeholder with GS, which is how FNC1 separators are encoded and decoded.
+fn replace_fnc1_placeholder(data: &str, fnc1_placeholder: &str) -> Option<String> {
+    let mut placeholder = fnc1_placeholder.chars();
+    let (Some(placeholder), None) = (placeholder.next(), placeholder.next()) else {
+        return None;
+    };
+
+    // The leading FNC1 is added by the encoder
+    let data = data.strip_prefix(placeholder).unwrap_or(data);
+    Some(data.replace(placeholder, &crate::datamatrix::GROUP_SEPARATOR.to_string()))
+}
+
+fn create_hints(options: &EncodeOptions, margin: u32) -> EncodingHintDictionary {
+    let mut hints: EncodingHintDictionary = HashMap::new();
 
     hints.insert(EncodeHintType::MARGIN, EncodeHintValue::Margin(margin.to_string()));
 
-    if let Some(error_correction) = options.error_correction {
-        hints.insert(EncodeHintType::ERROR_CORRECTION, EncodeHintValue::ErrorCorrection(error_correction));
+    if let Some(error_correction) = &options.error_correction {
+        hints.insert(EncodeHintType::ERROR_CORRECTION, EncodeHintValue::ErrorCorrection(error_correction.clone()));
     }
 
-    if let Some(character_set) = options.character_set {
-        hints.insert(EncodeHintType::CHARACTER_SET, EncodeHintValue::CharacterSet(character_set));
+    if let Some(character_set) = &options.character_set {
+        hints.insert(EncodeHintType::CHARACTER_SET, EncodeHintValue::CharacterSet(character_set.clone()));
     }
 
     if let Some(data_matrix_compact) = options.data_matrix_compact {
@@ -80,8 +740,8 @@ pub fn encode(data: String, options: Option<EncodeOptions>) -> Option<Buffer> {
         hints.insert(EncodeHintType::PDF417_COMPACT, EncodeHintValue::Pdf417Compact(pdf417_compact.to_string()));
     }
 
-    if let Some(pdf417_compaction) = options.pdf417_compaction {
-        hints.insert(EncodeHintType::PDF417_COMPACTION, EncodeHintValue::Pdf417Compaction(pdf417_compaction));
+    if let Some(pdf417_compaction) = &options.pdf417_compaction {
+        hints.insert(EncodeHintType::PDF417_COMPACTION, EncodeHintValue::Pdf417Compaction(pdf417_compaction.clone()));
     }
 
     if let Some(pdf417_auto_eci) = options.pdf417_auto_eci {
@@ -92,12 +752,12 @@ pub fn encode(data: String, options: Option<EncodeOptions>) -> Option<Buffer> {
         hints.insert(EncodeHintType::AZTEC_LAYERS, EncodeHintValue::AztecLayers(aztec_layers));
     }
 
-    if let Some(qr_version) = options.qr_version {
-        hints.insert(EncodeHintType::QR_VERSION, EncodeHintValue::QrVersion(qr_version));
+    if let Some(qr_version) = &options.qr_version {
+        hints.insert(EncodeHintType::QR_VERSION, EncodeHintValue::QrVersion(qr_version.clone()));
     }
 
-    if let Some(qr_mask_pattern) = options.qr_mask_pattern {
-        hints.insert(EncodeHintType::QR_MASK_PATTERN, EncodeHintValue::QrMaskPattern(qr_mask_pattern));
+    if let Some(qr_mask_pattern) = &options.qr_mask_pattern {
+        hints.insert(EncodeHintType::QR_MASK_PATTERN, EncodeHintValue::QrMaskPattern(qr_mask_pattern.clone()));
     }
 
     if let Some(qr_compact) = options.qr_compact {
@@ -108,8 +768,8 @@ pub fn encode(data: String, options: Option<EncodeOptions>) -> Option<Buffer> {
         hints.insert(EncodeHintType::GS1_FORMAT, EncodeHintValue::Gs1Format(gs1_format));
     }
 
-    if let Some(force_code_set) = options.force_code_set {
-        hints.insert(EncodeHintType::FORCE_CODE_SET, EncodeHintValue::ForceCodeSet(force_code_set));
+    if let Some(force_code_set) = &options.force_code_set {
+        hints.insert(EncodeHintType::FORCE_CODE_SET, EncodeHintValue::ForceCodeSet(force_code_set.clone()));
     }
 
     if let Some(force_c40) = options.force_c40 {
@@ -120,35 +780,464 @@ pub fn encode(data: String, options: Option<EncodeOptions>) -> Option<Buffer> {
         hints.insert(EncodeHintType::CODE128_COMPACT, EncodeHintValue::Code128Compact(code128_compact));
     }
 
-    let writer = MultiFormatWriter::default();
-    if let Ok(bit_matrix) = writer.encode_with_hints(
-        &data,
-        &barcode_format.into(),
-        width as i32,
-        height as i32,
-        &hints,
-    ) {
-        let image: DynamicImage = bit_matrix.into();
-        let mut bytes: Vec<u8> = Vec::new();
-
-        if image.write_to(&mut Cursor::new(&mut bytes), image::ImageOutputFormat::Jpeg(100)).is_ok() {
-            if let Some(file_path) = options.output_file {
-                if write_to_file(&file_path, &bytes).is_ok() {
-                    Some(Buffer::from(bytes))
-                } else {
-                    None
-                }
-            } else {
-                Some(Buffer::from(bytes))
+    hints
+}
+
+#[cfg(feature = "pdf")]
+#[napi(object)]
+pub struct PdfBatchItem {
+    /** The data to encode */
+    pub data: String,
+    /** Optional label printed below the code */
+    pub caption: Option<String>,
+}
+
+#[cfg(feature = "pdf")]
+#[napi(object)]
+#[derive(Default)]
+pub struct PdfBatchOptions {
+    /** Options used to encode every item, `width` and `height` set the raster resolution of each code */
+    pub encode: Option<EncodeOptions>,
+    /** Page width in millimeters, defaults to A4 (210) */
+    pub page_width_mm: Option<f64>,
+    /** Page height in millimeters, defaults to A4 (297) */
+    pub page_height_mm: Option<f64>,
+    /** Printed code width in millimeters, defaults to 50 */
+    pub code_width_mm: Option<f64>,
+    /** Printed code height in millimeters, defaults to keeping the aspect ratio of the encoded image */
+    pub code_height_mm: Option<f64>,
+}
+
+/**
+ * Encode a batch of barcodes into a single PDF document, one code per page
+ *
+ * @param {Array<PdfBatchItem>} items The data, and optional caption, of each code
+ * @param {PdfBatchOptions} [options] Optional encoding and page layout options
+ *
+ * @returns {Buffer|null} A buffer containing the PDF, or `null` if any of the barcodes could not be encoded
+ *
+ * @example
+ * const { encodePdfBatch } = require('@rxing/rxing');
+ * const fs = require('fs');
+ *
+ * const items = [{ data: 'VOUCHER-1', caption: 'Voucher #1' }, { data: 'VOUCHER-2', caption: 'Voucher #2' }];
+ * fs.writeFileSync('vouchers.pdf', encodePdfBatch(items, { codeWidthMm: 40 }));
+ */
+#[cfg(feature = "pdf")]
+#[napi]
+pub fn encode_pdf_batch(env: Env, items: Vec<PdfBatchItem>, options: Option<PdfBatchOptions>) -> Option<Buffer> {
+    let options = options.unwrap_or_default();
+    let encode_options = options.encode.unwrap_or_default();
+
+    let mut pages = Vec::with_capacity(items.len());
+    for item in items {
+        let image = DynamicImage::ImageRgb8(render(&item.data, &encode_options, None, &mut |warning| emit_warning(&env, warning))?.image.to_rgb8());
+        let mut jpeg: Vec<u8> = Vec::new();
+        image.write_to(&mut Cursor::new(&mut jpeg), image::ImageOutputFormat::Jpeg(100)).ok()?;
+
+        pages.push(crate::pdf::PdfPage {
+            jpeg,
+            image_width: image.width(),
+            image_height: image.height(),
+            caption: item.caption,
+        });
+    }
+
+    let code_width_mm = options.code_width_mm.unwrap_or(50.0);
+    let code_height_mm = options.code_height_mm.unwrap_or_else(|| {
+        pages.first().map_or(code_width_mm, |page| code_width_mm * page.image_height as f64 / page.image_width as f64)
+    });
+    let layout = crate::pdf::PdfLayout {
+        page_width_mm: options.page_width_mm.unwrap_or(210.0),
+        page_height_mm: options.page_height_mm.unwrap_or(297.0),
+        code_width_mm,
+        code_height_mm,
+    };
+
+    Some(Buffer::from(crate::pdf::write_pdf(&pages, &layout)))
+}
+
+/// Formats `encodePreviewSheet` tries, every format rxing can write.
+const PREVIEW_FORMATS: [JsBarcodeFormat; 13] = [
+    JsBarcodeFormat::AZTEC,
+    JsBarcodeFormat::CODABAR,
+    JsBarcodeFormat::Code39,
+    JsBarcodeFormat::Code93,
+    JsBarcodeFormat::Code128,
+    JsBarcodeFormat::DataMatrix,
+    JsBarcodeFormat::Ean8,
+    JsBarcodeFormat::Ean13,
+    JsBarcodeFormat::ITF,
+    JsBarcodeFormat::Pdf417,
+    JsBarcodeFormat::QrCode,
+    JsBarcodeFormat::UpcA,
+    JsBarcodeFormat::UpcE,
+];
+/// Blank space around every tile of a preview sheet, in pixels.
+const PREVIEW_PADDING: u32 = 16;
+/// Scale of the labels of a preview sheet, whose font is 7 pixels high.
+const PREVIEW_LABEL_SCALE: u32 = 2;
+/// Space between a code and its label on a preview sheet, in pixels.
+const PREVIEW_LABEL_GAP: u32 = 8;
+
+/**
+ * A contact sheet of the same data encoded in every format that can hold it
+ */
+#[napi(object)]
+pub struct PreviewSheet {
+    /** The sheet image, in `outputFormat` */
+    pub image: Buffer,
+    /** The formats on the sheet, in the order of their tiles, left to right then top to bottom */
+    pub formats: Vec<JsBarcodeFormat>,
+    /** The formats left off the sheet because they can't encode the data, such as EAN-13 for text */
+    pub skipped: Vec<JsBarcodeFormat>,
+}
+
+/**
+ * Encode the same data in every format rxing can write, and tile the codes into a single image with the name of
+ * their format below each
+ *
+ * The tiles are laid out in a grid as close to square as possible, in the order of `BarcodeFormat`. Formats that
+ * can't encode the data are left off the sheet and listed in `skipped`.
+ *
+ * @param {string} data The data to encode
+ * @param {EncodeOptions} [options] Optional options to pass to the encoder for every code, `barcodeFormat` is ignored
+ *
+ * @returns {PreviewSheet|null} The sheet and the formats on it, or `null` if no format could encode the data
+ *
+ * @example
+ * const { encodePreviewSheet } = require('@rxing/rxing');
+ * const fs = require('fs');
+ *
+ * const { image, skipped } = encodePreviewSheet('12345670', { width: 160, height: 160, outputFormat: 'png' });
+ * fs.writeFileSync('formats.png', image);
+ */
+#[napi]
+pub fn encode_preview_sheet(env: Env, data: String, options: Option<EncodeOptions>) -> Option<PreviewSheet> {
+    let options = options.unwrap_or_default();
+
+    let (mut tiles, mut skipped) = (Vec::new(), Vec::new());
+    for barcode_format in PREVIEW_FORMATS {
+        let tile_options = EncodeOptions { barcode_format: Some(barcode_format), ..options.clone() };
+        // Only the warnings of the codes on the sheet are reported, skipped formats are listed instead
+        let mut warnings = Vec::new();
+        match render(&data, &tile_options, None, &mut |warning| warnings.push(warning.to_string())) {
+            Some(rendering) => {
+                warnings.iter().for_each(|warning| emit_warning(&env, warning));
+                tiles.push((barcode_format, rendering.image.to_rgb8()));
             }
-        } else {
-            None
+            None => skipped.push(barcode_format),
         }
+    }
+    if tiles.is_empty() {
+        return None;
+    }
+
+    let label = |barcode_format: JsBarcodeFormat| BarcodeFormat::from(barcode_format).to_string();
+    let (label_width, label_height) = tiles.iter()
+        .map(|(barcode_format, _)| crate::font::text_size(&label(*barcode_format), PREVIEW_LABEL_SCALE))
+        .fold((0, 0), |(width, height), size| (width.max(size.0), height.max(size.1)));
+    let code_width = tiles.iter().map(|(_, image)| image.width()).max()?;
+    let code_height = tiles.iter().map(|(_, image)| image.height()).max()?;
+    let cell_width = code_width.max(label_width) + 2 * PREVIEW_PADDING;
+    let cell_height = code_height + PREVIEW_LABEL_GAP + label_height + 2 * PREVIEW_PADDING;
+
+    let columns = (tiles.len() as f64).sqrt().ceil() as u32;
+    let rows = (tiles.len() as u32).div_ceil(columns);
+    let mut sheet = RgbImage::from_pixel(columns * cell_width, rows * cell_height, WHITE);
+    for (index, (barcode_format, image)) in tiles.iter().enumerate() {
+        let (left, top) = ((index as u32 % columns) * cell_width, (index as u32 / columns) * cell_height);
+        sheet.copy_from(image, left + (cell_width - image.width()) / 2, top + PREVIEW_PADDING).ok()?;
+
+        let label = label(*barcode_format);
+        let (width, _) = crate::font::text_size(&label, PREVIEW_LABEL_SCALE);
+        let label_top = top + PREVIEW_PADDING + code_height + PREVIEW_LABEL_GAP;
+        crate::font::draw_text(&mut sheet, &label, left + (cell_width - width) / 2, label_top, PREVIEW_LABEL_SCALE, Rgb([0, 0, 0]));
+    }
+
+    let bytes = write_image(&DynamicImage::ImageRgb8(sheet), options.output_format.as_deref())?;
+    if let Some(file_path) = &options.output_file {
+        write_to_file(file_path, &bytes).ok()?;
+    }
+
+    Some(PreviewSheet { image: Buffer::from(bytes), formats: tiles.into_iter().map(|(barcode_format, _)| barcode_format).collect(), skipped })
+}
+
+/// Parses the foreground colors of `option`, checking that they would scan against a white background.
+fn parse_foreground_colors(colors: &[String], option: &str, allow_low_contrast: bool, warn: &mut dyn FnMut(&str)) -> Option<Vec<Rgb<u8>>> {
+    if colors.is_empty() {
+        return None;
+    }
+
+    let mut parsed = Vec::with_capacity(colors.len());
+    for color in colors {
+        let rgb = parse_color(color)?;
+        if !is_scannable(rgb, WHITE) {
+            let problem = format!(
+                "{option} color {color} is not dark enough against the white background (contrast ratio {:.1}:1, at least {MIN_CONTRAST_RATIO}:1 is needed)",
+                contrast_ratio(rgb, WHITE)
+            );
+            check_contrast(&problem, allow_low_contrast, warn)?;
+        }
+        parsed.push(rgb);
+    }
+
+    Some(parsed)
+}
+
+/// Reports colors unlikely to scan, failing unless `allowLowContrast` is set.
+fn check_contrast(problem: &str, allow_low_contrast: bool, warn: &mut dyn FnMut(&str)) -> Option<()> {
+    if allow_low_contrast {
+        warn(&format!("{problem}, it may not scan reliably"));
+        Some(())
     } else {
+        warn(&format!("{problem}, set allowLowContrast to render it anyway"));
         None
     }
 }
 
+/// Rasterizes the matrix cycling through `colors` for each module row. Module rows are found as runs of identical
+/// pixel rows, the shortest run inside the symbol giving the module height.
+fn render_row_colors(bit_matrix: &BitMatrix, colors: &[Rgb<u8>]) -> DynamicImage {
+    let (width, height) = (bit_matrix.getWidth(), bit_matrix.getHeight());
+    let mut image = RgbImage::from_pixel(width, height, WHITE);
+
+    let Some([_, top, _, symbol_height]) = bit_matrix.getEnclosingRectangle() else {
+        return DynamicImage::ImageRgb8(image);
+    };
+
+    let mut module_height = symbol_height;
+    let mut run = 1;
+    for y in top + 1..top + symbol_height {
+        if bit_matrix.getRow(y) == bit_matrix.getRow(y - 1) {
+            run += 1;
+        } else {
+            module_height = module_height.min(run);
+            run = 1;
+        }
+    }
+    let module_height = module_height.max(1);
+
+    for y in 0..height {
+        let color = colors[(y.saturating_sub(top) / module_height) as usize % colors.len()];
+        for x in 0..width {
+            if bit_matrix.get(x, y) {
+                image.put_pixel(x, y, color);
+            }
+        }
+    }
+
+    DynamicImage::ImageRgb8(image)
+}
+
+/// Paints the dark modules of the three finder patterns of a QR code. The module size is found from the top row of
+/// the top left pattern, which is a run of 7 dark modules.
+fn color_finder_patterns(image: &mut DynamicImage, bit_matrix: &BitMatrix, color: Rgb<u8>) -> Option<()> {
+    let [left, top, width, height] = bit_matrix.getEnclosingRectangle()?;
+    let run = (left..left + width).take_while(|&x| bit_matrix.get(x, top)).count() as u32;
+    let pattern_size = run / 7 * 7;
+    if pattern_size == 0 {
+        return None;
+    }
+
+    let corners = [(left, top), (left + width - pattern_size, top), (left, top + height - pattern_size)];
+    for (corner_x, corner_y) in corners {
+        for y in corner_y..corner_y + pattern_size {
+            for x in corner_x..corner_x + pattern_size {
+                if bit_matrix.get(x, y) {
+                    image.put_pixel(x, y, Rgba([color[0], color[1], color[2], u8::MAX]));
+                }
+            }
+        }
+    }
+
+    Some(())
+}
+
+/// Replaces the light pixels of `image` with `background`, of the same size, and returns the fraction of those
+/// pixels that lack contrast with the lightest foreground color.
+fn apply_background(image: &mut DynamicImage, bit_matrix: &BitMatrix, background: &RgbImage, foreground_colors: &[Rgb<u8>]) -> f64 {
+    let (width, height) = (image.width(), image.height());
+    let lightest_foreground = lightest_color(foreground_colors);
+
+    let (mut light_pixels, mut low_contrast_pixels) = (0u64, 0u64);
+    for y in 0..height {
+        for x in 0..width {
+            if bit_matrix.get(x, y) {
+                continue;
+            }
+
+            let pixel = *background.get_pixel(x, y);
+            light_pixels += 1;
+            if !is_scannable(lightest_foreground, pixel) {
+                low_contrast_pixels += 1;
+            }
+            image.put_pixel(x, y, Rgba([pixel[0], pixel[1], pixel[2], u8::MAX]));
+        }
+    }
+
+    if light_pixels == 0 {
+        0.0
+    } else {
+        low_contrast_pixels as f64 / light_pixels as f64
+    }
+}
+
+fn lightest_color(colors: &[Rgb<u8>]) -> Rgb<u8> {
+    colors.iter().copied()
+        .max_by(|a, b| relative_luminance(*a).total_cmp(&relative_luminance(*b)))
+        .unwrap_or(Rgb([0, 0, 0]))
+}
+
+/// Draws the dark modules of `image` at the given opacity over what lies under them, `background` or white.
+fn apply_module_opacity(image: &mut DynamicImage, bit_matrix: &BitMatrix, background: Option<&RgbImage>, opacity: f64) {
+    for y in 0..image.height() {
+        for x in 0..image.width() {
+            if !bit_matrix.get(x, y) {
+                continue;
+            }
+
+            let [red, green, blue, _] = image.get_pixel(x, y).0;
+            let under = background.map_or(WHITE, |background| *background.get_pixel(x, y));
+            let pixel = blend_color(Rgb([red, green, blue]), under, opacity);
+            image.put_pixel(x, y, Rgba([pixel[0], pixel[1], pixel[2], u8::MAX]));
+        }
+    }
+}
+
+/// Composites `foreground` over `background` with the given opacity.
+fn blend_color(foreground: Rgb<u8>, background: Rgb<u8>, opacity: f64) -> Rgb<u8> {
+    let blend = |index: usize| (foreground[index] as f64 * opacity + background[index] as f64 * (1.0 - opacity)).round() as u8;
+    Rgb([blend(0), blend(1), blend(2)])
+}
+
+/// Crops the matrix to the bounding box of its dark modules, leaving no quiet zone at all.
+fn crop_quiet_zone(bit_matrix: BitMatrix) -> BitMatrix {
+    let Some([left, top, width, height]) = bit_matrix.getEnclosingRectangle() else {
+        return bit_matrix;
+    };
+
+    let Ok(mut cropped) = BitMatrix::new(width, height) else {
+        return bit_matrix;
+    };
+    for y in 0..height {
+        for x in 0..width {
+            if bit_matrix.get(left + x, top + y) {
+                cropped.set(x, y);
+            }
+        }
+    }
+
+    cropped
+}
+
+fn apply_knockout(image: &mut DynamicImage, regions: &[Rect]) {
+    let (image_width, image_height) = (image.width(), image.height());
+
+    for region in regions {
+        let x_end = region.x.saturating_add(region.width).min(image_width);
+        let y_end = region.y.saturating_add(region.height).min(image_height);
+
+        for y in region.y.min(y_end)..y_end {
+            for x in region.x.min(x_end)..x_end {
+                image.put_pixel(x, y, Rgba([u8::MAX, u8::MAX, u8::MAX, u8::MAX]));
+            }
+        }
+    }
+}
+
+/// Decodes `image` and checks that it reads back as `data` in the given format.
+fn verify_image(image: &DynamicImage, data: &str, barcode_format: BarcodeFormat) -> bool {
+    let luma = image.to_luma8();
+    let (width, height) = luma.dimensions();
+
+    let mut hints: DecodingHintDictionary = HashMap::new();
+    hints.insert(DecodeHintType::POSSIBLE_FORMATS, DecodeHintValue::PossibleFormats(HashSet::from([barcode_format])));
+
+    match rxing::helpers::detect_in_luma_with_hints(luma.into_raw(), width, height, None, &mut hints) {
+        Ok(result) => result.getText() == canonical_contents(data, barcode_format),
+        Err(_) => false,
+    }
+}
+
+/// The contents as decoded: EAN and UPC codes encoded without their check digit decode with it appended.
+fn canonical_contents(data: &str, barcode_format: BarcodeFormat) -> Cow<'_, str> {
+    if !data.bytes().all(|b| b.is_ascii_digit()) {
+        return Cow::Borrowed(data);
+    }
+    let digits = match barcode_format {
+        BarcodeFormat::EAN_8 if data.len() == 7 => data.to_owned(),
+        BarcodeFormat::EAN_13 if data.len() == 12 => data.to_owned(),
+        BarcodeFormat::UPC_A if data.len() == 11 => data.to_owned(),
+        // The UPC-E check digit is that of the UPC-A code it expands to
+        BarcodeFormat::UPC_E if data.len() == 7 => match rxing::oned::convertUPCEtoUPCA(data) {
+            Some(upca) => upca,
+            None => return Cow::Borrowed(data),
+        },
+        _ => return Cow::Borrowed(data),
+    };
+
+    // Weighted 3 and 1 alternately from the right
+    let sum: u32 = digits.bytes().rev().enumerate().map(|(i, b)| (b - b'0') as u32 * if i % 2 == 0 { 3 } else { 1 }).sum();
+    Cow::Owned(format!("{data}{}", (10 - sum % 10) % 10))
+}
+
+/// Simulates printing `image` on white stock: a blur of a quarter of a module, dot gain widening the dark areas by
+/// about a tenth of a module, and noise.
+fn simulate_print_degradation(image: &DynamicImage) -> DynamicImage {
+    let luma = image.to_luma8();
+    let module_size = typical_dark_run(&luma) as f32;
+
+    // Codes rendered without a margin get the white stock around them as a quiet zone
+    let border = (module_size * 4.0).ceil() as u32;
+    let mut printed = GrayImage::from_pixel(luma.width() + 2 * border, luma.height() + 2 * border, Luma([u8::MAX]));
+    image::imageops::overlay(&mut printed, &luma, border as i64, border as i64);
+
+    let mut degraded = image::imageops::blur(&printed, (module_size * 0.25).max(0.5));
+    // Darkening the blurred edges moves them outwards, a gamma of 1.3 moving the midpoint of an edge blurred by a
+    // quarter of a module by a twentieth of a module. A fixed noise seed keeps the outcome reproducible
+    let mut state: u32 = 0x2545_F491;
+    for pixel in degraded.pixels_mut() {
+        let gained = (pixel[0] as f32 / 255.0).powf(1.3) * 255.0;
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        pixel[0] = (gained.round() as i32 + (state % 17) as i32 - 8).clamp(0, u8::MAX as i32) as u8;
+    }
+
+    DynamicImage::ImageLuma8(degraded)
+}
+
+/// The most common width of the dark runs along the rows of the image, which is the module size of most codes.
+fn typical_dark_run(luma: &GrayImage) -> u32 {
+    let mut run_counts: HashMap<u32, usize> = HashMap::new();
+    for row in luma.rows() {
+        let mut run = 0;
+        for pixel in row.chain([&Luma([u8::MAX])]) {
+            if pixel[0] < 128 {
+                run += 1;
+            } else if run > 0 {
+                *run_counts.entry(run).or_default() += 1;
+                run = 0;
+            }
+        }
+    }
+
+    run_counts.into_iter().max_by_key(|&(run, count)| (count, std::cmp::Reverse(run))).map_or(1, |(run, _)| run)
+}
+
+/// Emits a Node.js process warning, the same way `process.emitWarning` does.
+fn emit_warning(env: &Env, warning: &str) {
+    let emit = || -> napi::Result<()> {
+        let process: JsObject = env.get_global()?.get_named_property("process")?;
+        let emit_warning: JsFunction = process.get_named_property("emitWarning")?;
+        emit_warning.call(Some(&process), &[env.create_string(warning)?])?;
+        Ok(())
+    };
+
+    let _ = emit();
+}
+
 fn write_to_file(file_path: &str, bytes: &[u8]) -> Result<(), std::io::Error> {
     let mut file = File::create(file_path)?;
     file.write_all(bytes)
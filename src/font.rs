@@ -0,0 +1,84 @@
+//! A minimal 5x7 bitmap font, just enough to label codes with the names of their formats.
+
+use image::{Rgb, RgbImage};
+
+const GLYPH_WIDTH: u32 = 5;
+const GLYPH_HEIGHT: u32 = 7;
+/// Blank columns between two glyphs, before scaling.
+const GLYPH_SPACING: u32 = 1;
+
+/// Rows of the digits, then of the uppercase letters, top to bottom, with the leftmost pixel as the fifth bit.
+const GLYPHS: [[u8; GLYPH_HEIGHT as usize]; 36] = [
+    [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+    [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+    [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+    [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+    [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+    [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+    [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+    [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+    [0x0E, 0x11, 0x11, 0x11, 0x1F, 0x11, 0x11],
+    [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E],
+    [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E],
+    [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C],
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F],
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10],
+    [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F],
+    [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+    [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E],
+    [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C],
+    [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+    [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F],
+    [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11],
+    [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+    [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+    [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10],
+    [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D],
+    [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11],
+    [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E],
+    [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04],
+    [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A],
+    [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11],
+    [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04],
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F],
+];
+
+fn glyph(character: char) -> Option<&'static [u8; GLYPH_HEIGHT as usize]> {
+    match character.to_ascii_uppercase() {
+        digit @ '0'..='9' => Some(&GLYPHS[digit as usize - '0' as usize]),
+        letter @ 'A'..='Z' => Some(&GLYPHS[10 + letter as usize - 'A' as usize]),
+        _ => None,
+    }
+}
+
+/// Size in pixels of `text` drawn at `scale`.
+pub(crate) fn text_size(text: &str, scale: u32) -> (u32, u32) {
+    let characters = text.chars().count() as u32;
+    let width = (characters * (GLYPH_WIDTH + GLYPH_SPACING)).saturating_sub(GLYPH_SPACING);
+    (width * scale, GLYPH_HEIGHT * scale)
+}
+
+/// Draws `text` in uppercase with its top left corner at `(x, y)`, each font pixel being `scale` pixels wide.
+/// Characters other than ASCII letters and digits are left blank, and pixels outside the image are clipped.
+pub(crate) fn draw_text(image: &mut RgbImage, text: &str, x: u32, y: u32, scale: u32, color: Rgb<u8>) {
+    for (index, character) in text.chars().enumerate() {
+        let Some(rows) = glyph(character) else {
+            continue;
+        };
+        let left = x + index as u32 * (GLYPH_WIDTH + GLYPH_SPACING) * scale;
+        for (row, bits) in rows.iter().enumerate() {
+            for column in (0..GLYPH_WIDTH).filter(|column| bits & (0x10 >> column) != 0) {
+                for (dx, dy) in (0..scale).flat_map(|dx| (0..scale).map(move |dy| (dx, dy))) {
+                    let (pixel_x, pixel_y) = (left + column * scale + dx, y + row as u32 * scale + dy);
+                    if pixel_x < image.width() && pixel_y < image.height() {
+                        image.put_pixel(pixel_x, pixel_y, color);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,181 @@
+//! Parsing of GS1 element strings, the fields prefixed by an application identifier (AI) that GS1 barcodes carry.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use napi::bindgen_prelude::Either;
+
+use crate::datamatrix::GROUP_SEPARATOR;
+
+/// Symbology identifiers of the GS1 variants of GS1-128, GS1 DataBar, GS1 Data Matrix and GS1 QR Code.
+const GS1_SYMBOLOGY_IDENTIFIERS: [&str; 4] = ["]C1", "]e0", "]d2", "]Q3"];
+
+/// Whether a result with this symbology identifier carries GS1 element strings.
+pub(crate) fn is_gs1(symbology_identifier: &str) -> bool {
+    GS1_SYMBOLOGY_IDENTIFIERS.contains(&symbology_identifier)
+}
+
+/// Splits GS1 data into its (AI, value) pairs, either from the encoded form, where GS separates variable length
+/// fields, or from the human readable form, where every AI is in parentheses. Returns `None` for data using AIs
+/// of unknown length or with truncated fields.
+pub(crate) fn parse_element_strings(text: &str) -> Option<Vec<(String, String)>> {
+    if text.starts_with('(') {
+        return parse_human_readable(text);
+    }
+
+    let mut elements = Vec::new();
+    let mut rest = text.trim_start_matches(GROUP_SEPARATOR);
+    while !rest.is_empty() {
+        let ai_length = ai_length(rest)?;
+        let ai = rest.get(..ai_length).filter(|ai| ai.bytes().all(|byte| byte.is_ascii_digit()))?;
+        rest = &rest[ai_length..];
+
+        let value_length = match fixed_value_length(ai) {
+            Some(length) => length,
+            None => rest.find(GROUP_SEPARATOR).unwrap_or(rest.len()),
+        };
+        let value = rest.get(..value_length)?;
+        if value.is_empty() {
+            return None;
+        }
+        elements.push((ai.to_string(), value.to_string()));
+        // Fixed length fields may still be followed by a separator
+        rest = rest[value_length..].trim_start_matches(GROUP_SEPARATOR);
+    }
+
+    Some(elements)
+}
+
+fn parse_human_readable(text: &str) -> Option<Vec<(String, String)>> {
+    let mut elements = Vec::new();
+    for field in text.split('(').skip(1) {
+        let (ai, value) = field.split_once(')')?;
+        if ai.is_empty() || !ai.bytes().all(|byte| byte.is_ascii_digit()) {
+            return None;
+        }
+        elements.push((ai.to_string(), value.to_string()));
+    }
+
+    Some(elements)
+}
+
+/// Length of the AI at the start of `data`, from its first two digits.
+fn ai_length(data: &str) -> Option<usize> {
+    let prefix: u8 = data.get(..2)?.parse().ok()?;
+    match prefix {
+        0..=22 | 30 | 37 | 90..=99 => Some(2),
+        23..=25 | 40..=42 | 71 => Some(3),
+        31..=36 | 39 | 43 | 70 | 72 | 80..=82 => Some(4),
+        _ => None,
+    }
+}
+
+/// Length of the value of AIs with a predefined length, from the first two digits of the AI, as listed by the GS1
+/// General Specifications. Other values are terminated by a separator.
+fn fixed_value_length(ai: &str) -> Option<usize> {
+    match ai.get(..2)?.parse::<u8>().ok()? {
+        0 => Some(18),
+        1..=3 => Some(14),
+        4 => Some(16),
+        11..=19 => Some(6),
+        20 => Some(2),
+        31..=36 => Some(6),
+        41 => Some(13),
+        _ => None,
+    }
+}
+
+/// Converts the value of the AIs with a well known type: dates to ISO 8601 strings, counts and measures to numbers.
+/// Returns the raw value for other AIs, and for values that aren't valid for their AI.
+pub(crate) fn normalize_value(ai: &str, value: &str) -> Either<String, f64> {
+    normalize_known_value(ai, value).unwrap_or_else(|| Either::A(value.to_string()))
+}
+
+fn normalize_known_value(ai: &str, value: &str) -> Option<Either<String, f64>> {
+    let prefix: u8 = ai.get(..2)?.parse().ok()?;
+    match ai {
+        // Production, due, packaging, best before, sell by and expiration dates, and first freeze date
+        "11" | "12" | "13" | "15" | "16" | "17" | "7006" => parse_date(value).map(Either::A),
+        // Expiration date and time
+        "7003" => parse_date_time(value).map(Either::A),
+        // Variable count and count of trade items
+        "30" | "37" => parse_digits(value).map(Either::B),
+        // Trade measures and amounts payable, with the number of decimals as the last digit of the AI
+        _ if ai.len() == 4 && ((31..=36).contains(&prefix) || ai.starts_with("390") || ai.starts_with("392")) => {
+            let decimals = parse_digits(&ai[3..])? as i32;
+            Some(Either::B(parse_digits(value)? / 10f64.powi(decimals)))
+        }
+        _ => None,
+    }
+}
+
+fn parse_digits(value: &str) -> Option<f64> {
+    if value.is_empty() || !value.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    value.parse().ok()
+}
+
+/// Converts a GS1 `YYMMDD` date to `YYYY-MM-DD`, or to `YYYY-MM` when the day is `00`, which GS1 uses for dates
+/// that only specify a month.
+fn parse_date(value: &str) -> Option<String> {
+    if value.len() != 6 {
+        return None;
+    }
+    let year = parse_digits(value.get(..2)?)? as i64;
+    let (month, day) = (parse_digits(value.get(2..4)?)? as u32, parse_digits(value.get(4..)?)? as u32);
+    if !(1..=12).contains(&month) {
+        return None;
+    }
+
+    let year = expand_year(year, current_year());
+    match day {
+        0 => Some(format!("{year:04}-{month:02}")),
+        day if day <= days_in_month(year, month) => Some(format!("{year:04}-{month:02}-{day:02}")),
+        _ => None,
+    }
+}
+
+/// Converts a GS1 `YYMMDDHHMM` date and time to `YYYY-MM-DDTHH:MM`.
+fn parse_date_time(value: &str) -> Option<String> {
+    if value.len() != 10 {
+        return None;
+    }
+    // Unlike dates, the day can't be left out
+    let date = parse_date(value.get(..6)?).filter(|date| date.len() == "YYYY-MM-DD".len())?;
+    let (hours, minutes) = (parse_digits(value.get(6..8)?)?, parse_digits(value.get(8..)?)?);
+    (hours < 24.0 && minutes < 60.0).then(|| format!("{date}T{hours:02}:{minutes:02}"))
+}
+
+/// Picks the century of a two digit year as GS1 does, within 49 years in the past and 50 years in the future.
+fn expand_year(year: i64, current_year: i64) -> i64 {
+    let century = current_year - current_year.rem_euclid(100);
+    match year - current_year.rem_euclid(100) {
+        difference if difference >= 51 => century - 100 + year,
+        difference if difference <= -50 => century + 100 + year,
+        _ => century + year,
+    }
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// The current year in UTC.
+fn current_year() -> i64 {
+    let days = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs() / 86400) as i64;
+
+    // Howard Hinnant's days to civil date conversion, with years starting in March
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+
+    year_of_era + era * 400 + if month_index >= 10 { 1 } else { 0 }
+}
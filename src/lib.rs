@@ -4,8 +4,15 @@
 extern crate napi_derive;
 
 mod barcode_format;
+mod code128;
+mod color;
+mod datamatrix;
 mod encode;
+mod font;
+mod gs1;
 mod decode;
+#[cfg(feature = "pdf")]
+mod pdf;
 
 pub use barcode_format::*;
 pub use encode::*;
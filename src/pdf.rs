@@ -0,0 +1,127 @@
+//! A minimal PDF writer, just enough to lay out one barcode image and caption per page.
+
+use std::io::Write;
+
+const POINTS_PER_MM: f64 = 72.0 / 25.4;
+const CAPTION_FONT_SIZE: f64 = 12.0;
+const CAPTION_GAP_MM: f64 = 4.0;
+
+pub(crate) struct PdfPage {
+    /// Baseline JPEG data of an RGB image.
+    pub jpeg: Vec<u8>,
+    pub image_width: u32,
+    pub image_height: u32,
+    pub caption: Option<String>,
+}
+
+/// Physical page and code sizes, in millimeters.
+pub(crate) struct PdfLayout {
+    pub page_width_mm: f64,
+    pub page_height_mm: f64,
+    pub code_width_mm: f64,
+    pub code_height_mm: f64,
+}
+
+/// Writes a PDF with one page per entry, each with its code centered horizontally in the top half of the
+/// page and its caption, if any, centered below it.
+pub(crate) fn write_pdf(pages: &[PdfPage], layout: &PdfLayout) -> Vec<u8> {
+    let mut pdf = PdfWriter::default();
+    let page_ids: Vec<usize> = (0..pages.len()).map(|index| 4 + index * 3).collect();
+
+    pdf.object(1, b"<< /Type /Catalog /Pages 2 0 R >>");
+
+    let kids: Vec<String> = page_ids.iter().map(|id| format!("{id} 0 R")).collect();
+    pdf.object(2, format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), pages.len()).as_bytes());
+
+    pdf.object(3, b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>");
+
+    let page_width = layout.page_width_mm * POINTS_PER_MM;
+    let page_height = layout.page_height_mm * POINTS_PER_MM;
+    let code_width = layout.code_width_mm * POINTS_PER_MM;
+    let code_height = layout.code_height_mm * POINTS_PER_MM;
+
+    for (page, id) in pages.iter().zip(page_ids) {
+        let (content_id, image_id) = (id + 1, id + 2);
+
+        pdf.object(id, format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {page_width:.2} {page_height:.2}] \
+             /Resources << /Font << /F1 3 0 R >> /XObject << /Im0 {image_id} 0 R >> >> /Contents {content_id} 0 R >>"
+        ).as_bytes());
+
+        let code_x = (page_width - code_width) / 2.0;
+        let code_y = page_height / 2.0 + (page_height / 2.0 - code_height) / 2.0;
+        let mut content = format!("q {code_width:.2} 0 0 {code_height:.2} {code_x:.2} {code_y:.2} cm /Im0 Do Q\n");
+
+        if let Some(caption) = &page.caption {
+            // Helvetica glyphs average about half the font size in width, good enough to center a label
+            let text_width = caption.chars().count() as f64 * CAPTION_FONT_SIZE * 0.5;
+            let text_x = (page_width - text_width) / 2.0;
+            let text_y = code_y - CAPTION_GAP_MM * POINTS_PER_MM - CAPTION_FONT_SIZE;
+            content.push_str(&format!(
+                "BT /F1 {CAPTION_FONT_SIZE} Tf {text_x:.2} {text_y:.2} Td ({}) Tj ET\n",
+                escape_text(caption)
+            ));
+        }
+        pdf.stream(content_id, "", content.as_bytes());
+
+        pdf.stream(image_id, &format!(
+            "/Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceRGB /BitsPerComponent 8 /Filter /DCTDecode",
+            page.image_width, page.image_height
+        ), &page.jpeg);
+    }
+
+    pdf.finish()
+}
+
+/// Escapes a string for a PDF literal, replacing characters outside of WinAnsi's Latin-1 range.
+fn escape_text(text: &str) -> String {
+    text.chars().map(|c| match c {
+        '\\' | '(' | ')' => format!("\\{c}"),
+        c if (c as u32) < 0x20 || (c as u32) > 0xFF => "?".to_string(),
+        c if (c as u32) > 0x7E => format!("\\{:03o}", c as u32),
+        c => c.to_string(),
+    }).collect()
+}
+
+#[derive(Default)]
+struct PdfWriter {
+    bytes: Vec<u8>,
+    offsets: Vec<(usize, usize)>,
+}
+
+impl PdfWriter {
+    fn object(&mut self, id: usize, body: &[u8]) {
+        self.begin(id);
+        self.bytes.extend_from_slice(body);
+        self.bytes.extend_from_slice(b"\nendobj\n");
+    }
+
+    fn stream(&mut self, id: usize, dictionary: &str, data: &[u8]) {
+        self.begin(id);
+        let _ = write!(self.bytes, "<< {dictionary} /Length {} >>\nstream\n", data.len());
+        self.bytes.extend_from_slice(data);
+        self.bytes.extend_from_slice(b"\nendstream\nendobj\n");
+    }
+
+    fn begin(&mut self, id: usize) {
+        if self.bytes.is_empty() {
+            self.bytes.extend_from_slice(b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n");
+        }
+        self.offsets.push((id, self.bytes.len()));
+        let _ = writeln!(self.bytes, "{id} 0 obj");
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        self.offsets.sort_unstable();
+        let xref_offset = self.bytes.len();
+        let count = self.offsets.len() + 1;
+
+        let _ = write!(self.bytes, "xref\n0 {count}\n0000000000 65535 f \n");
+        for (_, offset) in &self.offsets {
+            let _ = writeln!(self.bytes, "{offset:010} 00000 n ");
+        }
+        let _ = write!(self.bytes, "trailer\n<< /Size {count} /Root 1 0 R >>\nstartxref\n{xref_offset}\n%%EOF\n");
+
+        self.bytes
+    }
+}